@@ -1,12 +1,34 @@
+use crate::cache::CachedSource;
+use crate::calendar::{self, Holiday, WeekParity};
+use crate::config::Settings;
+use crate::diff::{self, Change};
 use crate::event::{AppEvent, Event, EventHandler};
-use crossterm::event::KeyEventKind;
+use crate::fetch::{self, Fetched};
+use crate::filter;
+use crate::free::{self, FreeTime};
+use crate::groups::{self, GroupChoice, GroupStatus};
+use crate::keymap::{Action, Context, KeyBinding, Keymap};
+use crate::notes::{Note, NoteForm, NoteTarget, Notes};
+use crate::notification::{Notification, Notifications, Severity};
+use crate::palette::ColorMode;
+use crate::personal::{self, EventForm, PersonalEvent};
+use crate::reminder::{self, Reminders};
+use crate::schema::{self, BlockId, Decoded};
+use crate::search::Search;
+use crate::stats::Stats;
+use crate::theme::Theme;
+use crate::ui;
+use crate::validate::{self, Problem};
+use chrono::NaiveDate;
+use crossterm::event::{KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use rand::prelude::*;
 use ratatui::{
     DefaultTerminal,
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Position, Rect},
     style::Color,
 };
-use ureq::Error;
+use std::time::{Duration, Instant};
 
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
@@ -14,28 +36,86 @@ use serde_derive::Serialize;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeBlock {
+    /// Id the API gives the block, from payload version 2 on
+    #[serde(default)]
+    pub id: Option<String>,
     pub day: usize,
     pub time: usize,
+    /// Hours the block lasts, one if the API leaves it out
+    #[serde(default = "schema::one_hour")]
     pub duration: usize,
+    #[serde(default)]
     pub professor: String,
+    #[serde(default)]
     pub classroom: String,
     pub subject: Subject,
+    /// First date the block takes place on
+    #[serde(default)]
+    pub valid_from: Option<NaiveDate>,
+    /// Last date the block takes place on
+    #[serde(default)]
+    pub valid_until: Option<NaiveDate>,
+    /// Only in odd or even weeks
+    #[serde(default)]
+    pub weeks: Option<WeekParity>,
+    /// Dates the block is cancelled on
+    #[serde(default)]
+    pub except: Vec<NaiveDate>,
+    /// The only dates the block takes place on, for one-off sessions
+    #[serde(default)]
+    pub dates: Vec<NaiveDate>,
+    #[serde(skip)]
+    pub origin: Origin,
+}
+
+/// Where a block comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Origin {
+    /// The timetable API
+    #[default]
+    Source,
+    /// The user's own events
+    Personal,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subject {
     pub name: String,
+    #[serde(default)]
     pub abbreviation: String,
+    #[serde(default)]
     pub location: String,
+    /// Trimmed, the API pads it with whitespace
+    #[serde(default, deserialize_with = "schema::trimmed")]
     pub r#type: String,
 }
-#[derive(Debug, Clone)]
-pub struct Warning {
-    pub title: String,
-    pub message: String,
-    pub bottom_hint: String,
-    pub color: Color,
+/// Longest time between the two clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Popups drawn on top of the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Popup {
+    /// Toggles the filter rules, `cursor` indexes [`App::filter_entries`]
+    Filters { cursor: usize },
+    /// Every field of the selected block
+    Details,
+    /// The active keybindings, scrolled down by `scroll` lines
+    Help { scroll: u16 },
+    /// Every notification raised so far, newest first
+    Messages { scroll: u16 },
+    /// What changed in the last refresh
+    Changes { scroll: u16 },
+    /// Blocks of the timetable that are not drawn, scrolled down by `scroll` lines
+    Problems { scroll: u16 },
+    /// Hours of the classes of the week shown
+    Statistics,
+    /// The user's own events, `cursor` indexes [`App::personal`]
+    Events { cursor: usize },
+    /// Adding or editing one of the user's own events
+    EventForm(EventForm),
+    /// Writing the note of a subject or a block
+    NoteEditor(NoteForm),
 }
 
 /// Application.
@@ -46,20 +126,42 @@ pub struct App {
     pub events: EventHandler,
     pub timetable_data: Vec<TimeBlock>,
     pub colors: Vec<(String, Color)>,
-    pub warning: Option<Warning>,
-    pub config: Option<String>,
-}
-impl Default for Warning {
-    fn default() -> Self {
-        Warning {
-            message: "This is a warning".to_string(),
-            title: "Warning".to_string(),
-            bottom_hint: "Press <Esc> to close the window".to_string(),
-            color: Color::Yellow,
-        }
-    }
+    pub notifications: Notifications,
+    /// Timetable urls from the config file, one per line
+    pub sources: Vec<String>,
+    pub source_index: usize,
+    /// Source the timetable on screen was loaded from
+    pub loaded_source: Option<String>,
+    /// Differences between the timetable on screen and the one it replaced
+    pub changes: Vec<Change>,
+    /// Blocks of the timetable on screen that could not be drawn
+    pub problems: Vec<Problem>,
+    /// Index of the selected block in [`App::visible_blocks`]
+    pub selected: Option<usize>,
+    pub settings: Settings,
+    /// Whether `settings.json` could not be read, so it is not overwritten with the defaults
+    pub settings_unreadable: bool,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub color_mode: ColorMode,
+    pub popup: Option<Popup>,
+    pub search: Option<Search>,
+    /// Size of the last drawn frame, to find what was clicked
+    pub area: Rect,
+    /// Time and position of the last click, to detect double clicks
+    pub last_click: Option<(Instant, u16, u16)>,
+    pub reminders: Reminders,
+    /// The user's own events, drawn alongside the timetable
+    pub personal: Vec<PersonalEvent>,
+    pub notes: Notes,
+    /// Holidays from the settings and the holiday files
+    pub holidays: Vec<Holiday>,
+    /// Weeks between the current week and the one shown
+    pub week_offset: i64,
+    /// Class about to start and the minutes left until it does
+    pub alert: Option<(TimeBlock, i64)>,
+    pub free_time: FreeTime,
 }
-
 impl Default for App {
     fn default() -> Self {
         let mut init = Self {
@@ -68,26 +170,133 @@ impl Default for App {
             events: EventHandler::new(),
             timetable_data: Vec::new(),
             colors: Vec::new(),
-            warning: None,
-            config: None,
+            notifications: Notifications::default(),
+            sources: Vec::new(),
+            source_index: 0,
+            loaded_source: None,
+            changes: Vec::new(),
+            problems: Vec::new(),
+            selected: None,
+            settings: Settings::default(),
+            settings_unreadable: false,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            color_mode: ColorMode::TrueColor,
+            popup: None,
+            search: None,
+            area: Rect::default(),
+            last_click: None,
+            reminders: Reminders::default(),
+            personal: Vec::new(),
+            notes: Notes::default(),
+            holidays: Vec::new(),
+            week_offset: 0,
+            alert: None,
+            free_time: FreeTime::default(),
+        };
+        match Settings::load() {
+            Ok(settings) => {
+                let (keymap, problems) = Keymap::load(&settings.keymap);
+                if !problems.is_empty() {
+                    init.notify(Severity::Warn, "Keymap", problems.join("\n"));
+                }
+                init.keymap = keymap;
+                init.holidays = settings.holidays.clone();
+                for path in &settings.holiday_files {
+                    match calendar::load_holidays(path) {
+                        Ok(holidays) => init.holidays.extend(holidays),
+                        Err(message) => init.notify(Severity::Warn, "Holidays", message),
+                    }
+                }
+                init.settings = settings;
+            }
+            Err(message) => {
+                init.settings_unreadable = true;
+                init.notify(
+                    Severity::Error,
+                    "Error",
+                    format!(
+                        "{}\nChanges to the settings are not saved until it is fixed",
+                        message
+                    ),
+                );
+            }
+        }
+        // Also with the default settings, so NO_COLOR and the terminal are respected
+        init.color_mode = ColorMode::from_setting(init.settings.color_mode);
+        let theme = match Theme::resolve(&init.settings.theme) {
+            Ok((theme, problems)) => {
+                if !problems.is_empty() {
+                    init.notify(Severity::Warn, "Theme", problems.join("\n"));
+                }
+                theme
+            }
+            Err(message) => {
+                init.notify(Severity::Warn, "Theme", message);
+                Theme::default()
+            }
         };
+        init.theme = theme.mapped(init.color_mode);
+        match personal::load() {
+            Ok(events) => init.personal = events,
+            Err(message) => init.notify(Severity::Error, "Error", message),
+        }
+        match Notes::load() {
+            Ok(notes) => init.notes = notes,
+            Err(message) => init.notify(Severity::Error, "Error", message),
+        }
         init.events.send(AppEvent::Refresh);
         init
     }
 }
 
+#[cfg(test)]
 impl TimeBlock {
-    // TODO: Make the api trim the type
+    /// A block of the API with only the fields most tests care about.
+    pub fn sample(day: usize, time: usize, duration: usize, name: &str, r#type: &str) -> Self {
+        TimeBlock {
+            id: None,
+            day,
+            time,
+            duration,
+            professor: "Professor".into(),
+            classroom: "P1".into(),
+            subject: Subject {
+                name: name.into(),
+                abbreviation: name.chars().take(3).collect(),
+                location: "FRI".into(),
+                r#type: r#type.into(),
+            },
+            valid_from: None,
+            valid_until: None,
+            weeks: None,
+            except: Vec::new(),
+            dates: Vec::new(),
+            origin: Origin::Source,
+        }
+    }
+}
+
+impl TimeBlock {
+    /// Whether the block takes place on `date`, which is assumed to fall on its day.
+    pub fn takes_place(&self, date: NaiveDate) -> bool {
+        self.valid_from.is_none_or(|from| from <= date)
+            && self.valid_until.is_none_or(|until| date <= until)
+            && self.weeks.is_none_or(|weeks| weeks.matches(date))
+            && !self.except.contains(&date)
+            && (self.dates.is_empty() || self.dates.contains(&date))
+    }
+
     pub fn format_block(&self) -> String {
+        if self.origin == Origin::Personal {
+            return format!("{}\nLoc: {}", &self.subject.name, &self.classroom);
+        }
         format!(
             "{}\n\
         {}\n\
         Type: {}\n\
         Loc: {}",
-            &self.professor,
-            &self.subject.name,
-            &self.subject.r#type.trim(),
-            &self.classroom,
+            &self.professor, &self.subject.name, &self.subject.r#type, &self.classroom,
         )
     }
 }
@@ -96,160 +305,948 @@ impl App {
     /// Run the application's main loop.
     pub async fn run(mut self, mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
         while self.running {
-            terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
+            let frame = terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
+            self.area = frame.area;
             match self.events.next().await? {
                 Event::Tick => self.tick(),
-                Event::Crossterm(event) => {
-                    if let crossterm::event::Event::Key(key_event) = event {
-                        self.handle_key_events(key_event)?
+                Event::Crossterm(event) => match event {
+                    crossterm::event::Event::Key(key_event) => self.handle_key_events(key_event)?,
+                    crossterm::event::Event::Mouse(mouse_event) => {
+                        self.handle_mouse_events(mouse_event)
                     }
-                }
+                    _ => {}
+                },
                 Event::App(app_event) => match app_event {
                     AppEvent::Quit => self.quit(),
-                    AppEvent::Refresh => {
-                        self.config = App::check_config();
-                        'fetching_json: {
-                            // http://localhost:8080/timetable/fri/61310
-                            // let url = "http://localhost:8080/timetable/fri/61310";
-                            if let Some(url) = self.config.clone() {
-                                match ureq::get(url).call() {
-                                    Ok(mut valid) => {
-                                        let data = valid.body_mut().read_to_string().unwrap();
-
-                                        _ = data.trim();
-                                        let json: Vec<TimeBlock> =
-                                            serde_json::from_str(&data).unwrap();
-                                        self.timetable_data = json;
-                                        'colors: {
-                                            if !self.timetable_data.is_empty() {
-                                                let mut rng = rand::rng();
-                                                let mut colors_rand: Vec<(String, Color)> =
-                                                    Vec::new();
-                                                let mut subjects: Vec<String> = self
-                                                    .timetable_data
-                                                    .clone()
-                                                    .iter()
-                                                    .map(|entry| entry.subject.name.clone())
-                                                    .collect();
-                                                if !subjects.is_empty() {
-                                                    subjects.sort();
-                                                    subjects.dedup();
-                                                }
-                                                for sub in subjects {
-                                                    let temp_color = Color::Rgb(
-                                                        rng.random_range(50..=255),
-                                                        rng.random_range(100..=255),
-                                                        rng.random_range(0..=255),
-                                                    );
-                                                    colors_rand.push((sub, temp_color))
-                                                }
-                                                self.colors = colors_rand;
-                                            }
-                                        }
-                                    }
-                                    Err(Error::StatusCode(code)) => {
-                                        if code == 429 {
-                                            self.warning = Some(Warning {
-                                                message: "Too many requests...".into(),
-                                                title: "Error".into(),
-                                                color: Color::Yellow,
-                                                bottom_hint: "Press <Esc> to close the window"
-                                                    .into(),
-                                            });
-                                        } else if code == 404 {
-                                            self.warning = Some(Warning {
-                                                message: "Page not found".into(),
-                                                title: "Error".into(),
-                                                color: Color::Red,
-                                                bottom_hint: "Press <Esc> to close the window"
-                                                    .into(),
-                                            });
-                                        } else if code == 408 {
-                                            self.warning = Some(Warning {
-                                                message: "Request timed out...".into(),
-                                                title: "Error".into(),
-                                                color: Color::Red,
-                                                bottom_hint: "Press <Esc> to close the window"
-                                                    .into(),
-                                            });
-                                        }
-                                    }
-                                    Err(_) => {
-                                        self.warning = Some(Warning {
-                                            message: "Host unreachable\n\
-                                            Check if the config url works"
-                                                .into(),
-                                            title: "Error".into(),
-                                            color: Color::Red,
-                                            bottom_hint: "Press <Esc> to close the window".into(),
-                                        });
-                                    }
-                                }
-                            } else {
-                                let mut ret = "Empty config file...\n\
-                                    Add your api provider in:\n\
-                                    "
-                                .to_string();
-                                if let Some(cfg_dir) = dirs::config_dir() {
-                                    let str = cfg_dir
-                                        .join("tab-term")
-                                        .join("config.txt")
-                                        .display()
-                                        .to_string();
-                                    ret.push_str(&str);
-                                }
-                                ret.push_str(
-                                    "\n\
-                                    \n\
-                                    Example:\n\
-                                    http://localhost:8080/timetable/fri/61310
-                                    ",
-                                );
-                                self.warning = Some(Warning {
-                                    message: ret,
-                                    title: "Error".into(),
-                                    color: Color::Red,
-                                    bottom_hint: "Press <Esc> to close the window".into(),
-                                });
-                            }
-                        }
-                    } // _ => {}
+                    AppEvent::Refresh => self.refresh(),
                 },
             }
         }
         Ok(())
     }
 
+    /// Fetches the timetable of the current source.
+    fn refresh(&mut self) {
+        self.sources = App::check_config();
+        if self.source_index >= self.sources.len() {
+            self.source_index = 0;
+        }
+        // http://localhost:8080/timetable/fri/61310
+        let Some(url) = self.source().cloned() else {
+            let mut ret = "Empty config file...\n\
+                Add your api provider in:\n\
+                "
+            .to_string();
+            if let Some(cfg_dir) = dirs::config_dir() {
+                let str = cfg_dir
+                    .join("tab-term")
+                    .join("config.txt")
+                    .display()
+                    .to_string();
+                ret.push_str(&str);
+            }
+            ret.push_str(
+                "\n\
+                \n\
+                One url per line, example:\n\
+                http://localhost:8080/timetable/fri/61310
+                ",
+            );
+            self.notify(Severity::Error, "Error", ret);
+            return;
+        };
+        let http = self.settings.http.get(&url).cloned().unwrap_or_default();
+        let cached = CachedSource::load(&url);
+        match fetch::fetch(&url, &http, cached.as_ref()) {
+            Ok(Fetched::Modified(fresh)) => {
+                if let Err(message) = fresh.save() {
+                    self.notify(Severity::Warn, "Cache", message);
+                }
+                // Compare with the timetable on screen, or the valid blocks of the one seen last time
+                let previous = if self.loaded_source.as_ref() == Some(&url) {
+                    Some(self.timetable_data.clone())
+                } else {
+                    cached
+                        .and_then(|cached| schema::decode(&cached.body).ok())
+                        .map(|decoded| validate::validate(decoded.blocks).0)
+                };
+                if !self.load_body(&url, &fresh.body) {
+                    return;
+                }
+                self.notify(
+                    Severity::Info,
+                    "Refreshed",
+                    format!("Loaded {} blocks", self.timetable_data.len()),
+                );
+                self.changes = previous
+                    .map(|previous: Vec<TimeBlock>| diff::diff(&previous, &self.timetable_data))
+                    .unwrap_or_default();
+                if !self.changes.is_empty() {
+                    self.notify(
+                        Severity::Warn,
+                        "Timetable changed",
+                        format!(
+                            "{}\nPress <{}> to see the changes",
+                            diff::summary(&self.changes),
+                            self.keymap.key_hint(Context::Grid, Action::Changes)
+                        ),
+                    );
+                }
+            }
+            // Only parse the cached copy if another source is on screen
+            Ok(Fetched::NotModified) => {
+                if let Some(cached) = cached.filter(|_| self.loaded_source.as_ref() != Some(&url))
+                    && !self.load_body(&url, &cached.body)
+                {
+                    return;
+                }
+                self.notify(Severity::Info, "Refreshed", "Timetable unchanged");
+            }
+            Err(err) => {
+                self.notify(err.severity, err.title.clone(), err.describe());
+                // Show the last known timetable rather than nothing
+                if let Some(cached) = cached.filter(|_| self.loaded_source.as_ref() != Some(&url))
+                    && self.load_body(&url, &cached.body)
+                {
+                    self.notify(
+                        Severity::Info,
+                        "Offline",
+                        "Showing the last downloaded timetable",
+                    );
+                }
+            }
+        }
+    }
+
+    /// Shows the timetable in `body`, returns whether it could be read.
+    fn load_body(&mut self, url: &str, body: &str) -> bool {
+        let Decoded { blocks, skipped } = match schema::decode(body) {
+            Ok(decoded) => decoded,
+            Err(message) => {
+                self.notify(Severity::Error, "Error", message);
+                return false;
+            }
+        };
+        if self.notes.migrate(url, &blocks)
+            && let Err(message) = self.notes.save()
+        {
+            self.notify(Severity::Error, "Error", message);
+        }
+        let (blocks, invalid) = validate::validate(blocks);
+        self.problems = skipped.into_iter().chain(invalid).collect();
+        if !self.problems.is_empty() {
+            self.notify(
+                Severity::Warn,
+                "Timetable",
+                format!(
+                    "Skipped {} invalid block{}\nPress <{}> to see why",
+                    self.problems.len(),
+                    if self.problems.len() == 1 { "" } else { "s" },
+                    self.keymap.key_hint(Context::Grid, Action::Problems)
+                ),
+            );
+        }
+        self.loaded_source = Some(url.to_string());
+        self.set_timetable(blocks);
+        true
+    }
+
+    /// Replaces the timetable and gives every subject a color.
+    fn set_timetable(&mut self, blocks: Vec<TimeBlock>) {
+        let selected = self.selected_block().map(|block| self.block_id(&block));
+        self.timetable_data = blocks;
+        self.reselect(selected);
+        if !self.timetable_data.is_empty() {
+            let mut rng = rand::rng();
+            let mut colors_rand: Vec<(String, Color)> = Vec::new();
+            let mut subjects: Vec<String> = self
+                .timetable_data
+                .iter()
+                .map(|entry| entry.subject.name.clone())
+                .collect();
+            subjects.sort();
+            subjects.dedup();
+            for sub in subjects {
+                let temp_color = Color::Rgb(
+                    rng.random_range(50..=255),
+                    rng.random_range(100..=255),
+                    rng.random_range(0..=255),
+                );
+                colors_rand.push((sub, self.color_mode.map(temp_color)))
+            }
+            self.colors = colors_rand;
+        }
+    }
+
     /// Handles the key events and updates the state of [`App`].
     pub fn handle_key_events(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
-        if key_event.kind == KeyEventKind::Press {
-            match key_event.code {
-                KeyCode::Char('q') => self.events.send(AppEvent::Quit),
-                KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => {
-                    self.events.send(AppEvent::Quit)
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        // Ctrl+C always quits, whatever the keymap says
+        if matches!(key_event.code, KeyCode::Char('c' | 'C'))
+            && key_event.modifiers == KeyModifiers::CONTROL
+        {
+            self.events.send(AppEvent::Quit);
+            return Ok(());
+        }
+        let key = KeyBinding::from(key_event);
+        if self.notifications.current().is_some() {
+            match self.keymap.action(Context::Popup, key) {
+                Some(Action::Quit) => self.events.send(AppEvent::Quit),
+                Some(Action::Close) => self.notifications.dismiss(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(popup) = self.popup.clone() {
+            self.handle_popup_key_events(popup, key);
+            return Ok(());
+        }
+        if let Some(search) = self.search.as_mut().filter(|search| search.editing) {
+            match self.keymap.action(Context::Search, key) {
+                Some(Action::Cancel) => self.search = None,
+                Some(Action::Confirm) => search.editing = false,
+                Some(Action::DeleteChar) => {
+                    search.query.pop();
+                    self.jump_to_match(0);
                 }
-                KeyCode::Char('r') if self.warning.is_none() => self.events.send(AppEvent::Refresh),
-                KeyCode::Esc if self.warning.is_some() => {
-                    self.warning = None;
+                Some(_) => {}
+                None => {
+                    if let KeyCode::Char(c) = key.code {
+                        search.query.push(c);
+                        self.jump_to_match(0);
+                    }
                 }
-                _ => {}
             }
+            return Ok(());
+        }
+        let Some(action) = self.keymap.action(Context::Grid, key) else {
+            return Ok(());
+        };
+        match action {
+            Action::Quit => self.events.send(AppEvent::Quit),
+            Action::Refresh => self.events.send(AppEvent::Refresh),
+            Action::Filters => self.popup = Some(Popup::Filters { cursor: 0 }),
+            Action::Details if self.selected_block().is_some() => self.popup = Some(Popup::Details),
+            Action::Help => self.popup = Some(Popup::Help { scroll: 0 }),
+            Action::Messages => self.popup = Some(Popup::Messages { scroll: 0 }),
+            Action::Changes => self.popup = Some(Popup::Changes { scroll: 0 }),
+            Action::Problems => self.popup = Some(Popup::Problems { scroll: 0 }),
+            Action::Statistics => self.popup = Some(Popup::Statistics),
+            Action::Events => self.popup = Some(Popup::Events { cursor: 0 }),
+            Action::Search => {
+                self.search = Some(Search {
+                    editing: true,
+                    ..Default::default()
+                })
+            }
+            Action::NextMatch if self.search.is_some() => self.jump_to_match(1),
+            Action::PreviousMatch if self.search.is_some() => self.jump_to_match(-1),
+            Action::ClearSearch => self.search = None,
+            Action::NextWeek => self.switch_week(1),
+            Action::PreviousWeek => self.switch_week(-1),
+            Action::NextSource => self.switch_source(1),
+            Action::PreviousSource => self.switch_source(self.sources.len().saturating_sub(1)),
+            Action::Down => self.select_next(1),
+            Action::Up => self.select_next(-1),
+            Action::Right => self.select_next_day(1),
+            Action::Left => self.select_next_day(-1),
+            Action::ToggleGroup => self.toggle_group(),
+            Action::MuteSubject => self.toggle_mute(),
+            Action::FreeTime => self.toggle_free_time(),
+            Action::Note => self.edit_note(false),
+            Action::SubjectNote => self.edit_note(true),
+            Action::ToggleOtherGroups => {
+                self.settings.hide_other_groups = !self.settings.hide_other_groups;
+                self.save_settings();
+                self.clamp_selection();
+            }
+            _ => {}
         }
         Ok(())
     }
 
+    /// Handles clicks and the scroll wheel.
+    ///
+    /// A click selects the block under the cursor, a second click on it opens its details.
+    pub fn handle_mouse_events(&mut self, mouse_event: MouseEvent) {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let now = Instant::now();
+                let last_click =
+                    self.last_click
+                        .replace((now, mouse_event.column, mouse_event.row));
+                if self.notifications.current().is_some() {
+                    return;
+                }
+                // Clicking outside of a popup closes it, clicks inside of it do nothing yet
+                if let Some(popup) = &self.popup {
+                    let position = Position::new(mouse_event.column, mouse_event.row);
+                    if !ui::popup_rect(popup, self.area).contains(position) {
+                        self.popup = None;
+                    }
+                    return;
+                }
+                let blocks = self.visible_blocks();
+                let Some(idx) = ui::block_at(self, self.area, mouse_event.column, mouse_event.row)
+                    .and_then(|block| blocks.iter().position(|other| *other == block))
+                else {
+                    return;
+                };
+                let double_click = last_click.is_some_and(|(at, column, row)| {
+                    now.duration_since(at) <= DOUBLE_CLICK
+                        && ui::block_at(self, self.area, column, row).as_ref() == blocks.get(idx)
+                });
+                self.selected = Some(idx);
+                if double_click {
+                    self.last_click = None;
+                    self.popup = Some(Popup::Details);
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll(1),
+            MouseEventKind::ScrollUp => self.scroll(-1),
+            _ => {}
+        }
+    }
+
+    /// Scrolls the open popup, or moves the selection if there is none.
+    fn scroll(&mut self, by: isize) {
+        if self.notifications.current().is_some() {
+            return;
+        }
+        let action = if by > 0 { Action::Down } else { Action::Up };
+        match self.popup.clone() {
+            Some(popup) => self.handle_popup_action(popup, Some(action)),
+            None => self.select_next(by),
+        }
+    }
+
+    /// The url of the timetable currently shown.
+    pub fn source(&self) -> Option<&String> {
+        self.sources.get(self.source_index)
+    }
+
+    /// Moves `by` sources forward, wrapping around, and fetches the new source.
+    fn switch_source(&mut self, by: usize) {
+        if self.sources.len() < 2 {
+            return;
+        }
+        self.source_index = (self.source_index + by) % self.sources.len();
+        self.timetable_data.clear();
+        self.loaded_source = None;
+        self.changes.clear();
+        self.problems.clear();
+        self.selected = None;
+        self.events.send(AppEvent::Refresh);
+    }
+
+    /// Monday of the week shown in the grid.
+    pub fn week_start(&self) -> NaiveDate {
+        calendar::week_start(chrono::Local::now().date_naive(), self.week_offset)
+    }
+
+    /// Moves `by` weeks forward, keeping the selected block selected if it takes place then.
+    fn switch_week(&mut self, by: i64) {
+        let selected = self.selected_block().map(|block| self.block_id(&block));
+        self.week_offset += by;
+        self.reselect(selected);
+    }
+
+    /// Selects the block with the id again after the visible blocks changed.
+    fn reselect(&mut self, id: Option<BlockId>) {
+        let blocks = self.visible_blocks();
+        let found = id.and_then(|id| blocks.iter().position(|block| self.block_id(block) == id));
+        match found {
+            Some(idx) => self.selected = Some(idx),
+            None => self.clamp_selection(),
+        }
+    }
+
+    pub fn selected_block(&self) -> Option<TimeBlock> {
+        self.selected
+            .and_then(|idx| self.visible_blocks().get(idx).cloned())
+    }
+
+    /// Keeps the selection inside the visible blocks after they changed.
+    fn clamp_selection(&mut self) {
+        let len = self.visible_blocks().len();
+        self.selected = match self.selected {
+            _ if len == 0 => None,
+            Some(idx) => Some(idx.min(len - 1)),
+            None => None,
+        };
+    }
+
+    /// Moves the selection through the blocks in chronological order.
+    fn select_next(&mut self, by: isize) {
+        let len = self.visible_blocks().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = Some(match self.selected {
+            Some(idx) => idx.saturating_add_signed(by).min(len - 1),
+            None => 0,
+        });
+    }
+
+    /// Moves the selection to the block closest in time on the next day that has any blocks.
+    fn select_next_day(&mut self, by: isize) {
+        let blocks = self.visible_blocks();
+        let Some(current) = self.selected_block() else {
+            self.select_next(0);
+            return;
+        };
+        let mut day = current.day;
+        while let Some(next) = day.checked_add_signed(by).filter(|d| *d < 5) {
+            day = next;
+            let closest = blocks
+                .iter()
+                .enumerate()
+                .filter(|(_, block)| block.day == day)
+                .min_by_key(|(_, block)| block.time.abs_diff(current.time));
+            if let Some((idx, _)) = closest {
+                self.selected = Some(idx);
+                return;
+            }
+        }
+    }
+
+    /// Selects the `by`-th match counted from the selection, wrapping around.
+    ///
+    /// With `by` set to 0 the selection moves to the first match at or after it.
+    fn jump_to_match(&mut self, by: isize) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let matches = search.match_indices(&self.visible_blocks());
+        if matches.is_empty() {
+            return;
+        }
+        let current = self.selected.unwrap_or(0);
+        // Position of the first match at or after the selection
+        let at = matches.iter().position(|idx| *idx >= current).unwrap_or(0);
+        let pos = match by {
+            0 => at,
+            _ if matches.get(at) == Some(&current) => {
+                (at as isize + by).rem_euclid(matches.len() as isize) as usize
+            }
+            // The selection is not a match, so `at` already counts as one step forward
+            _ if by < 0 => (at as isize + by).rem_euclid(matches.len() as isize) as usize,
+            _ => (at as isize + by - 1).rem_euclid(matches.len() as isize) as usize,
+        };
+        self.selected = Some(matches[pos]);
+    }
+
+    /// Marks the selected block as the attended one of its parallel sessions, or clears the
+    /// choice if it already was.
+    fn toggle_group(&mut self) {
+        let Some(block) = self.selected_block() else {
+            return;
+        };
+        let Some(source) = self.source().cloned() else {
+            return;
+        };
+        if !groups::has_parallel(&self.timetable_data, &block) {
+            self.notify(
+                Severity::Info,
+                "My groups",
+                format!(
+                    "{} ({}) has no parallel sessions",
+                    block.subject.name, block.subject.r#type
+                ),
+            );
+            return;
+        }
+        let choices = self.settings.groups.entry(source).or_default();
+        let was_chosen = choices.iter().any(|choice| choice.is_chosen(&block));
+        choices.retain(|choice| !choice.same_group(&block));
+        if !was_chosen {
+            choices.push(GroupChoice::from_block(&block));
+        }
+        self.save_settings();
+    }
+
+    /// Stops or resumes the reminders for the subject of the selected block.
+    fn toggle_mute(&mut self) {
+        let Some(block) = self.selected_block() else {
+            return;
+        };
+        let muted = &mut self.settings.reminders.muted;
+        let message = match muted.iter().position(|name| *name == block.subject.name) {
+            Some(idx) => {
+                muted.remove(idx);
+                format!("Reminders for {} are back on", block.subject.name)
+            }
+            None => {
+                muted.push(block.subject.name.clone());
+                format!("No more reminders for {}", block.subject.name)
+            }
+        };
+        self.save_settings();
+        self.notify(Severity::Info, "Reminders", message);
+    }
+
+    /// How the block relates to the group choices of the current source.
+    pub fn group_status(&self, block: &TimeBlock) -> GroupStatus {
+        match self.source() {
+            Some(source) => self.group_status_in(source, block),
+            None => GroupStatus::Unset,
+        }
+    }
+
+    /// How a block of `source` relates to the group choices made for it.
+    fn group_status_in(&self, source: &str, block: &TimeBlock) -> GroupStatus {
+        match self.settings.groups.get(source) {
+            Some(choices) => groups::status(choices, block),
+            None => GroupStatus::Unset,
+        }
+    }
+
+    /// Shows the free time of this timetable, then of every timetable, then none.
+    fn toggle_free_time(&mut self) {
+        let message = match self.free_time {
+            FreeTime::Off => {
+                self.free_time = FreeTime::Own;
+                "Showing the free time of this timetable".to_string()
+            }
+            FreeTime::Own if self.sources.len() > 1 => {
+                let timetables = self.cached_timetables();
+                let message = format!(
+                    "Showing the time free in {} of {} timetables",
+                    timetables.len(),
+                    self.sources.len()
+                );
+                self.free_time = FreeTime::Everyone(timetables);
+                message
+            }
+            _ => {
+                self.free_time = FreeTime::Off;
+                "Free time hidden".to_string()
+            }
+        };
+        self.notify(Severity::Info, "Free time", message);
+    }
+
+    /// The last downloaded blocks of every source, by url.
+    ///
+    /// Sources that were never downloaded are left out and reported.
+    fn cached_timetables(&mut self) -> Vec<(String, Vec<TimeBlock>)> {
+        let mut timetables = Vec::new();
+        let mut missing = Vec::new();
+        for url in &self.sources {
+            match CachedSource::load(url).and_then(|cached| schema::decode(&cached.body).ok()) {
+                Some(decoded) => {
+                    timetables.push((url.clone(), validate::validate(decoded.blocks).0));
+                }
+                None => missing.push(url.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            self.notify(
+                Severity::Warn,
+                "Free time",
+                format!(
+                    "Switch to these timetables once to download them:\n{}",
+                    missing.join("\n")
+                ),
+            );
+        }
+        timetables
+    }
+
+    /// Hours without classes of the week shown, free in every timetable with
+    /// [`FreeTime::Everyone`].
+    pub fn free_hours(&self) -> Vec<Vec<(usize, usize)>> {
+        let monday = self.week_start();
+        let mut busy = self.attended_blocks(monday);
+        if let FreeTime::Everyone(timetables) = &self.free_time {
+            // The timetable on screen is already counted, as it is shown
+            let others = timetables
+                .iter()
+                .filter(|(source, _)| self.loaded_source.as_ref() != Some(source));
+            for (source, blocks) in others {
+                busy.extend(
+                    filter::apply(&self.settings.filters, blocks)
+                        .into_iter()
+                        .filter(|block| {
+                            self.takes_place_in(block, monday)
+                                && self.group_status_in(source, block) != GroupStatus::Other
+                        }),
+                );
+            }
+        }
+        free::free_hours(&busy)
+    }
+
+    fn handle_popup_key_events(&mut self, popup: Popup, key: KeyBinding) {
+        match popup {
+            Popup::EventForm(form) => self.handle_event_form_key(form, key),
+            Popup::NoteEditor(form) => self.handle_note_editor_key(form, key),
+            popup => {
+                let action = self.keymap.action(Context::Popup, key);
+                self.handle_popup_action(popup, action);
+            }
+        }
+    }
+
+    /// Edits the fields of a form with the key, returning the action if it confirms or cancels
+    /// the form.
+    fn type_into(
+        &self,
+        values: &mut [String],
+        field: &mut usize,
+        key: KeyBinding,
+    ) -> Option<Action> {
+        let fields = values.len();
+        match self.keymap.action(Context::Input, key) {
+            Some(action @ (Action::Confirm | Action::Cancel)) => return Some(action),
+            Some(Action::DeleteChar) => {
+                values[*field].pop();
+            }
+            Some(Action::NextField) => *field = (*field + 1) % fields,
+            Some(Action::PreviousField) => *field = (*field + fields - 1) % fields,
+            Some(_) => {}
+            None => {
+                if let KeyCode::Char(c) = key.code {
+                    values[*field].push(c);
+                }
+            }
+        }
+        None
+    }
+
+    /// Types into the event form, saving the event on confirm.
+    fn handle_event_form_key(&mut self, mut form: EventForm, key: KeyBinding) {
+        match self.type_into(&mut form.values, &mut form.field, key) {
+            Some(Action::Cancel) => {
+                self.popup = Some(Popup::Events {
+                    cursor: form
+                        .editing
+                        .unwrap_or(self.personal.len().saturating_sub(1)),
+                });
+                return;
+            }
+            Some(Action::Confirm) => {
+                match form.parse() {
+                    Ok(event) => {
+                        let cursor = match form.editing {
+                            Some(idx) if idx < self.personal.len() => {
+                                self.personal[idx] = event;
+                                idx
+                            }
+                            _ => {
+                                self.personal.push(event);
+                                self.personal.len() - 1
+                            }
+                        };
+                        self.save_personal();
+                        self.popup = Some(Popup::Events { cursor });
+                    }
+                    Err(message) => self.notify(Severity::Warn, "Event", message),
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.popup = Some(Popup::EventForm(form));
+    }
+
+    /// Types into the note editor, saving the note on confirm.
+    fn handle_note_editor_key(&mut self, mut form: NoteForm, key: KeyBinding) {
+        match self.type_into(&mut form.values, &mut form.field, key) {
+            Some(Action::Cancel) => self.popup = None,
+            Some(_) => {
+                self.notes.set(form.target.clone(), form.note());
+                if let Err(message) = self.notes.save() {
+                    self.notify(Severity::Error, "Error", message);
+                }
+                self.popup = None;
+            }
+            None => self.popup = Some(Popup::NoteEditor(form)),
+        }
+    }
+
+    /// Opens the note editor for the selected block, or for its whole subject.
+    fn edit_note(&mut self, subject: bool) {
+        let Some(block) = self.selected_block() else {
+            return;
+        };
+        let (target, label) = if subject {
+            (
+                NoteTarget::Subject(block.subject.name.clone()),
+                block.subject.name.clone(),
+            )
+        } else {
+            (
+                NoteTarget::Block(self.block_id(&block)),
+                format!("{} {}:00", block.subject.name, block.time),
+            )
+        };
+        let note = self.notes.get(&target);
+        self.popup = Some(Popup::NoteEditor(NoteForm::new(target, label, note)));
+    }
+
+    /// Identity of a block of the timetable on screen or of the user's own events.
+    pub fn block_id(&self, block: &TimeBlock) -> BlockId {
+        let source = match block.origin {
+            Origin::Source => self.loaded_source.as_deref().unwrap_or_default(),
+            Origin::Personal => "personal",
+        };
+        BlockId::new(source, block)
+    }
+
+    /// The notes shown for the block: the note of its subject and its own note.
+    pub fn notes_for(&self, block: &TimeBlock) -> (Option<&Note>, Option<&Note>) {
+        (
+            self.notes
+                .get(&NoteTarget::Subject(block.subject.name.clone())),
+            self.notes.get(&NoteTarget::Block(self.block_id(block))),
+        )
+    }
+
+    fn save_personal(&mut self) {
+        if let Err(message) = personal::save(&self.personal) {
+            self.notify(Severity::Error, "Error", message);
+        }
+        self.clamp_selection();
+    }
+
+    fn handle_popup_action(&mut self, popup: Popup, action: Option<Action>) {
+        match action {
+            Some(Action::Quit) => return self.events.send(AppEvent::Quit),
+            Some(Action::Close) => return self.popup = None,
+            _ => {}
+        }
+        match popup {
+            Popup::Filters { cursor } => {
+                let entries = self.filter_entries();
+                match action {
+                    Some(Action::Up) => {
+                        self.popup = Some(Popup::Filters {
+                            cursor: cursor.saturating_sub(1),
+                        })
+                    }
+                    Some(Action::Down) => {
+                        self.popup = Some(Popup::Filters {
+                            cursor: (cursor + 1).min(entries.len().saturating_sub(1)),
+                        })
+                    }
+                    Some(Action::Toggle) => {
+                        if let Some(rule) = entries.get(cursor) {
+                            filter::toggle(&mut self.settings.filters, rule.clone());
+                            self.save_settings();
+                            self.clamp_selection();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Popup::Details | Popup::Statistics => {}
+            Popup::Help { scroll } => {
+                let scroll = scrolled(scroll, action, self.help_lines() as usize);
+                self.popup = Some(Popup::Help { scroll });
+            }
+            Popup::Messages { scroll } => {
+                let scroll = scrolled(scroll, action, self.notifications.log.len());
+                self.popup = Some(Popup::Messages { scroll });
+            }
+            Popup::Events { cursor } => match action {
+                Some(Action::Up) => {
+                    self.popup = Some(Popup::Events {
+                        cursor: cursor.saturating_sub(1),
+                    })
+                }
+                Some(Action::Down) => {
+                    self.popup = Some(Popup::Events {
+                        cursor: (cursor + 1).min(self.personal.len().saturating_sub(1)),
+                    })
+                }
+                Some(Action::New) => self.popup = Some(Popup::EventForm(EventForm::default())),
+                Some(Action::Edit | Action::Toggle) => {
+                    if let Some(event) = self.personal.get(cursor) {
+                        self.popup = Some(Popup::EventForm(EventForm::edit(cursor, event)));
+                    }
+                }
+                Some(Action::Delete) if cursor < self.personal.len() => {
+                    self.personal.remove(cursor);
+                    self.save_personal();
+                    self.popup = Some(Popup::Events {
+                        cursor: cursor.min(self.personal.len().saturating_sub(1)),
+                    });
+                }
+                _ => {}
+            },
+            Popup::EventForm(_) | Popup::NoteEditor(_) => {}
+            Popup::Changes { scroll } => {
+                let scroll = scrolled(scroll, action, self.changes.len());
+                self.popup = Some(Popup::Changes { scroll });
+            }
+            Popup::Problems { scroll } => {
+                let scroll = scrolled(scroll, action, self.problems.len() * 2);
+                self.popup = Some(Popup::Problems { scroll });
+            }
+        }
+    }
+
+    /// The help overlay as (key, description) rows grouped under their context.
+    pub fn help_sections(&self) -> Vec<(Context, Vec<(String, &'static str)>)> {
+        Context::ALL
+            .iter()
+            .map(|context| {
+                let rows = self
+                    .keymap
+                    .actions(*context)
+                    .into_iter()
+                    .map(|(action, keys)| {
+                        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+                        (keys.join(", "), action.description())
+                    })
+                    .collect();
+                (*context, rows)
+            })
+            .collect()
+    }
+
+    fn help_lines(&self) -> u16 {
+        self.help_sections()
+            .iter()
+            // A header and an empty line per section
+            .map(|(_, rows)| rows.len() as u16 + 2)
+            .sum()
+    }
+
+    /// Every filter rule offered in the filter popup.
+    pub fn filter_entries(&self) -> Vec<filter::FilterRule> {
+        filter::entries(&self.settings.filters, &self.timetable_data)
+    }
+
+    /// The blocks that should be drawn in the grid, in chronological order.
+    pub fn visible_blocks(&self) -> Vec<TimeBlock> {
+        self.blocks_of_week(self.week_start())
+    }
+
+    /// The blocks taking place in the week starting at `monday` that pass the filters, together
+    /// with the user's own events, leaving out holidays.
+    pub fn blocks_of_week(&self, monday: NaiveDate) -> Vec<TimeBlock> {
+        let mut blocks = filter::apply(&self.settings.filters, &self.timetable_data);
+        blocks.extend(self.personal.iter().map(PersonalEvent::to_block));
+        blocks.retain(|block| self.takes_place_in(block, monday));
+        if self.settings.hide_other_groups {
+            blocks.retain(|block| self.group_status(block) != GroupStatus::Other);
+        }
+        blocks.sort_by(|a, b| {
+            (a.day, a.time, &a.subject.name).cmp(&(b.day, b.time, &b.subject.name))
+        });
+        blocks
+    }
+
+    /// Whether the block takes place in the week starting at `monday`, holidays included.
+    fn takes_place_in(&self, block: &TimeBlock, monday: NaiveDate) -> bool {
+        let date = calendar::date_of(monday, block.day);
+        block.takes_place(date) && calendar::holiday_on(&self.holidays, date).is_none()
+    }
+
+    /// The blocks of the week the user attends, leaving out parallel sessions of other groups.
+    pub fn attended_blocks(&self, monday: NaiveDate) -> Vec<TimeBlock> {
+        self.blocks_of_week(monday)
+            .into_iter()
+            .filter(|block| self.group_status(block) != GroupStatus::Other)
+            .collect()
+    }
+
+    /// Statistics of the classes attended in the week shown.
+    pub fn statistics(&self) -> Stats {
+        let classes: Vec<TimeBlock> = self
+            .attended_blocks(self.week_start())
+            .into_iter()
+            .filter(|block| block.origin == Origin::Source)
+            .collect();
+        let mut stats = Stats::compute(&classes);
+        stats.unpicked_groups = classes.iter().any(|block| {
+            self.group_status(block) == GroupStatus::Unset && groups::has_parallel(&classes, block)
+        });
+        stats
+    }
+
+    fn save_settings(&mut self) {
+        if self.settings_unreadable {
+            return self.notify(
+                Severity::Warn,
+                "Settings",
+                "Not saved, as the settings file could not be read when starting",
+            );
+        }
+        if let Err(message) = self.settings.save() {
+            self.notify(Severity::Error, "Error", message);
+        }
+    }
+
     /// Handles the tick event of the terminal.
     ///
     /// The tick event is where you can update the state of your application with any logic that
     /// needs to be updated at a fixed frame rate. E.g. polling a server, updating an animation.
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        self.notifications.expire(Instant::now());
+        let now = chrono::Local::now();
+        if !self.reminders.due(now) {
+            return;
+        }
+        // This week, whichever week is shown
+        let blocks = self.attended_blocks(calendar::week_start(now.date_naive(), 0));
+        if self.settings.reminders.enabled
+            && let Some(err) =
+                self.reminders
+                    .check(&self.settings.reminders, &blocks, &self.holidays, now)
+        {
+            self.notify(
+                Severity::Warn,
+                "Reminders",
+                format!("Could not send a desktop notification\n{}", err),
+            );
+        }
+        let alert = &self.settings.alert;
+        let next = alert
+            .enabled
+            .then(|| {
+                reminder::next_class(
+                    &blocks,
+                    &self.settings.reminders.muted,
+                    alert.lead_minutes,
+                    now,
+                )
+            })
+            .flatten();
+        // Ring once when a new class comes up
+        let new_class =
+            next.as_ref().map(|(block, _)| block) != self.alert.as_ref().map(|(block, _)| block);
+        if alert.bell && new_class && next.is_some() {
+            print!("\x07");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+        self.alert = next;
+    }
+
+    /// Raises a notification: info messages become toasts, everything else a popup.
+    pub fn notify(
+        &mut self,
+        severity: Severity,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.notifications
+            .push(Notification::new(severity, title, message));
+    }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
-    pub fn check_config() -> Option<String> {
+    /// Reads the timetable urls from the config file, creating an empty one if it is missing.
+    ///
+    /// Every non-empty line that does not start with `#` is a source.
+    pub fn check_config() -> Vec<String> {
         if let Some(cfg_dir) = dirs::config_dir() {
             let exists = cfg_dir.join("tab-term").join("config.txt");
             if !exists.is_file() {
@@ -262,14 +1259,24 @@ impl App {
 
         if let Some(cfg_dir) = dirs::config_dir() {
             let config_file = cfg_dir.join("tab-term").join("config.txt");
-            let file_contents: String =
-                std::fs::read_to_string(config_file).unwrap_or_else(|_| "~~~~".to_string());
-            if file_contents == "~~~~" || file_contents.is_empty() {
-                return None;
-            }
-            return Some(file_contents.trim().to_string());
+            let file_contents = std::fs::read_to_string(config_file).unwrap_or_default();
+            file_contents
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.to_string())
+                .collect()
         } else {
-            None
+            Vec::new()
         }
     }
 }
+
+/// The scroll position of a popup with `lines` lines after moving up or down.
+fn scrolled(scroll: u16, action: Option<Action>, lines: usize) -> u16 {
+    match action {
+        Some(Action::Up) => scroll.saturating_sub(1),
+        Some(Action::Down) => (scroll + 1).min(lines.saturating_sub(1) as u16),
+        _ => scroll,
+    }
+}
@@ -0,0 +1,43 @@
+use crate::config;
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The last timetable downloaded from a source, with the validators to ask the server whether
+/// it changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CachedSource {
+    pub url: String,
+    /// Value of the `ETag` response header
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` response header
+    pub last_modified: Option<String>,
+    /// The response body as received
+    pub body: String,
+}
+
+impl CachedSource {
+    /// One file per source, named after its url.
+    fn path(url: &str) -> Option<PathBuf> {
+        let name: String = url
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        dirs::cache_dir().map(|dir| dir.join("tab-term").join(format!("{}.json", name)))
+    }
+
+    /// The cached timetable of `url`, if there is a readable one.
+    pub fn load(url: &str) -> Option<CachedSource> {
+        config::read_json::<CachedSource>(CachedSource::path(url), "cache")
+            .ok()
+            .flatten()
+            .filter(|cached| cached.url == url)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = CachedSource::path(&self.url) else {
+            return Err("No cache directory available".into());
+        };
+        config::write_json(&path, self)
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::{Datelike, Days, NaiveDate};
+use serde_derive::{Deserialize, Serialize};
+
+/// Blocks that only take place every other week, by ISO week number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WeekParity {
+    Odd,
+    Even,
+}
+
+impl WeekParity {
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        let odd = date.iso_week().week() % 2 == 1;
+        match self {
+            WeekParity::Odd => odd,
+            WeekParity::Even => !odd,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeekParity::Odd => "Odd weeks",
+            WeekParity::Even => "Even weeks",
+        }
+    }
+}
+
+/// Monday of the week `offset` weeks away from the week of `today`.
+pub fn week_start(today: NaiveDate, offset: i64) -> NaiveDate {
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let days = Days::new(offset.unsigned_abs() * 7);
+    if offset < 0 {
+        monday - days
+    } else {
+        monday + days
+    }
+}
+
+/// Date of the `day`-th day of the week starting at `monday`.
+pub fn date_of(monday: NaiveDate, day: usize) -> NaiveDate {
+    monday + Days::new(day as u64)
+}
+
+/// A holiday, exam period or any other stretch of days without classes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holiday {
+    pub name: String,
+    pub from: NaiveDate,
+    /// Last day without classes, the same as `from` if not set
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+}
+
+impl Holiday {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.until.unwrap_or(self.from)
+    }
+}
+
+/// The holiday `date` falls into, if any.
+pub fn holiday_on(holidays: &[Holiday], date: NaiveDate) -> Option<&Holiday> {
+    holidays.iter().find(|holiday| holiday.contains(date))
+}
+
+/// Reads holidays from an iCalendar file, by its `.ics` extension, or a JSON list of holidays.
+pub fn load_holidays(path: &str) -> Result<Vec<Holiday>, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|err| format!("Could not read {}\n{}", path, err))?;
+    if path.to_lowercase().ends_with(".ics") {
+        parse_ics(&contents).map_err(|err| format!("Invalid calendar in {}\n{}", path, err))
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|err| format!("Invalid holidays in {}\n{}", path, err))
+    }
+}
+
+/// Turns every event of an iCalendar file into a holiday, keeping only the dates.
+fn parse_ics(contents: &str) -> Result<Vec<Holiday>, String> {
+    // Long lines are folded by starting the continuation with a space or a tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut holidays = Vec::new();
+    // Name, start and end of the event being read
+    let mut event: Option<(String, Option<NaiveDate>, Option<NaiveDate>)> = None;
+    for line in lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as `DTSTART;VALUE=DATE` are not needed
+        let key = key.split(';').next().unwrap_or_default();
+        match (key, value) {
+            ("BEGIN", "VEVENT") => event = Some((String::new(), None, None)),
+            ("END", "VEVENT") => {
+                let Some((name, Some(from), until)) = event.take() else {
+                    return Err("An event has no start date".into());
+                };
+                holidays.push(Holiday {
+                    name,
+                    from,
+                    until: until.filter(|until| *until > from),
+                });
+            }
+            _ => {
+                let Some((name, from, until)) = event.as_mut() else {
+                    continue;
+                };
+                match key {
+                    "SUMMARY" => *name = value.replace(r"\,", ",").replace(r"\;", ";"),
+                    "DTSTART" => *from = Some(parse_ics_date(value)?),
+                    // The end of an all-day event is the day after it
+                    "DTEND" if value.len() == 8 => {
+                        *until = parse_ics_date(value)?.pred_opt();
+                    }
+                    "DTEND" => *until = Some(parse_ics_date(value)?),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(holidays)
+}
+
+/// Reads the date of `20251224` or `20251224T090000Z`.
+fn parse_ics_date(value: &str) -> Result<NaiveDate, String> {
+    let date = value.get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| format!("Invalid date `{}`", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::TimeBlock;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    #[test]
+    fn weeks_alternate_by_iso_week_number() {
+        // Monday of week 43 and of week 44
+        assert!(WeekParity::Odd.matches(date(10, 20)));
+        assert!(!WeekParity::Even.matches(date(10, 20)));
+        assert!(WeekParity::Even.matches(date(10, 27)));
+        assert!(WeekParity::Odd.matches(date(10, 26)));
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(week_start(date(10, 22), 0), date(10, 20));
+        assert_eq!(week_start(date(10, 26), 0), date(10, 20));
+        assert_eq!(week_start(date(10, 20), 1), date(10, 27));
+        assert_eq!(week_start(date(10, 22), -3), date(9, 29));
+        assert_eq!(date_of(date(10, 20), 4), date(10, 24));
+    }
+
+    #[test]
+    fn blocks_take_place_within_their_dates() {
+        let mut block = TimeBlock::sample(0, 8, 2, "Math", "P");
+        assert!(block.takes_place(date(10, 20)));
+        block.valid_from = Some(date(10, 6));
+        block.valid_until = Some(date(12, 15));
+        assert!(!block.takes_place(date(9, 29)));
+        assert!(block.takes_place(date(10, 6)));
+        assert!(block.takes_place(date(12, 15)));
+        assert!(!block.takes_place(date(12, 22)));
+    }
+
+    #[test]
+    fn blocks_skip_other_weeks_and_cancelled_dates() {
+        let mut block = TimeBlock::sample(0, 8, 2, "Math", "P");
+        block.weeks = Some(WeekParity::Even);
+        block.except = vec![date(11, 10)];
+        assert!(!block.takes_place(date(10, 20)));
+        assert!(block.takes_place(date(10, 27)));
+        assert!(!block.takes_place(date(11, 10)));
+        assert!(block.takes_place(date(11, 24)));
+    }
+
+    #[test]
+    fn one_off_blocks_take_place_only_on_their_dates() {
+        let mut block = TimeBlock::sample(0, 8, 2, "Math", "P");
+        block.dates = vec![date(11, 3)];
+        assert!(block.takes_place(date(11, 3)));
+        assert!(!block.takes_place(date(11, 10)));
+    }
+
+    #[test]
+    fn holidays_last_until_their_last_day() {
+        let holidays = [
+            Holiday {
+                name: "Reformation Day".into(),
+                from: date(10, 31),
+                until: None,
+            },
+            Holiday {
+                name: "Christmas break".into(),
+                from: date(12, 24),
+                until: Some(date(12, 31)),
+            },
+        ];
+        let name = |date| holiday_on(&holidays, date).map(|holiday| holiday.name.as_str());
+        assert_eq!(name(date(10, 31)), Some("Reformation Day"));
+        assert_eq!(name(date(11, 1)), None);
+        assert_eq!(name(date(12, 24)), Some("Christmas break"));
+        assert_eq!(name(date(12, 31)), Some("Christmas break"));
+        assert_eq!(name(date(12, 23)), None);
+    }
+
+    #[test]
+    fn calendars_are_read_as_holidays() {
+        // The second summary is folded, which drops the first space of the continuation
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Christmas\\, New Year\r\n\
+            DTSTART;VALUE=DATE:20251224\r\n\
+            DTEND;VALUE=DATE:20260103\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Reformation\r\n  \
+            Day\r\n\
+            DTSTART;VALUE=DATE:20251031\r\n\
+            DTEND;VALUE=DATE:20251101\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        assert_eq!(
+            parse_ics(ics),
+            Ok(vec![
+                Holiday {
+                    name: "Christmas, New Year".into(),
+                    from: date(12, 24),
+                    until: NaiveDate::from_ymd_opt(2026, 1, 2),
+                },
+                Holiday {
+                    name: "Reformation Day".into(),
+                    from: date(10, 31),
+                    until: None,
+                },
+            ])
+        );
+        assert_eq!(
+            parse_ics("BEGIN:VEVENT\nSUMMARY:Nothing\nEND:VEVENT\n"),
+            Err("An event has no start date".into())
+        );
+    }
+}
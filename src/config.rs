@@ -0,0 +1,91 @@
+use crate::calendar::Holiday;
+use crate::fetch::HttpConfig;
+use crate::filter::FilterRule;
+use crate::groups::GroupChoice;
+use crate::keymap::KeymapConfig;
+use crate::palette::ColorModeSetting;
+use crate::reminder::{AlertConfig, ReminderConfig};
+use crate::theme::ThemeConfig;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Directory holding every configuration file of tab-term.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("tab-term"))
+}
+
+/// Directory holding the data the user enters in the app.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tab-term"))
+}
+
+/// Reads the JSON file at `path`, `None` if there is no such file.
+///
+/// `what` names the contents in errors, e.g. `settings`.
+pub fn read_json<T: serde::de::DeserializeOwned>(
+    path: Option<PathBuf>,
+    what: &str,
+) -> Result<Option<T>, String> {
+    let Some(path) = path.filter(|path| path.is_file()) else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Could not read {}\n{}", path.display(), err))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| format!("Invalid {} in {}\n{}", what, path.display(), err))
+}
+
+/// Writes `value` to `path` as JSON, creating the directory it is in.
+pub fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let contents = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    std::fs::write(path, contents)
+        .map_err(|err| format!("Could not write {}\n{}", path.display(), err))
+}
+
+/// User settings, stored as `settings.json` next to `config.txt`.
+///
+/// Every field has a default, so a missing file or missing keys are fine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// Values hidden from the grid
+    pub filters: Vec<FilterRule>,
+    /// Chosen parallel sessions, keyed by source url
+    pub groups: BTreeMap<String, Vec<GroupChoice>>,
+    /// Hide the parallel sessions that were not chosen instead of dimming them
+    pub hide_other_groups: bool,
+    pub keymap: KeymapConfig,
+    pub theme: ThemeConfig,
+    pub color_mode: ColorModeSetting,
+    /// HTTP options, keyed by source url
+    pub http: BTreeMap<String, HttpConfig>,
+    pub reminders: ReminderConfig,
+    pub alert: AlertConfig,
+    /// Days without classes
+    pub holidays: Vec<Holiday>,
+    /// `.ics` or JSON files to read more holidays from
+    pub holiday_files: Vec<String>,
+}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("settings.json"))
+    }
+
+    /// Reads the settings file, falling back to the defaults if it does not exist yet.
+    pub fn load() -> Result<Settings, String> {
+        read_json(Settings::path(), "settings").map(Option::unwrap_or_default)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = Settings::path() else {
+            return Err("No config directory available".into());
+        };
+        write_json(&path, self)
+    }
+}
@@ -0,0 +1,397 @@
+use crate::app::TimeBlock;
+use crate::ui::DAYS;
+use chrono::NaiveDate;
+
+/// What happened to a block between two versions of the timetable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(TimeBlock),
+    Removed(TimeBlock),
+    /// Now on another day, at another time or with another duration
+    Moved {
+        old: TimeBlock,
+        new: TimeBlock,
+    },
+    RoomChanged {
+        old: TimeBlock,
+        new: TimeBlock,
+    },
+    ProfessorChanged {
+        old: TimeBlock,
+        new: TimeBlock,
+    },
+    /// Takes place on other dates, in other weeks or was cancelled on some
+    ScheduleChanged {
+        old: TimeBlock,
+        new: TimeBlock,
+    },
+}
+
+impl Change {
+    /// The block as it is in the new timetable, `None` if it was removed.
+    pub fn current(&self) -> Option<&TimeBlock> {
+        match self {
+            Change::Removed(_) => None,
+            Change::Added(new)
+            | Change::Moved { new, .. }
+            | Change::RoomChanged { new, .. }
+            | Change::ProfessorChanged { new, .. }
+            | Change::ScheduleChanged { new, .. } => Some(new),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Change::Added(_) => "Added",
+            Change::Removed(_) => "Removed",
+            Change::Moved { .. } => "Moved",
+            Change::RoomChanged { .. } => "Room",
+            Change::ProfessorChanged { .. } => "Professor",
+            Change::ScheduleChanged { .. } => "Dates",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Added(block) | Change::Removed(block) => {
+                format!("{} {} in {}", name(block), when(block), block.classroom)
+            }
+            Change::Moved { old, new } => {
+                let mut text = format!("{} from {} to {}", name(new), when(old), when(new));
+                if old.classroom != new.classroom {
+                    text.push_str(&format!(", now in {}", new.classroom));
+                }
+                text
+            }
+            Change::RoomChanged { old, new } => format!(
+                "{} {} from {} to {}",
+                name(new),
+                when(new),
+                old.classroom,
+                new.classroom
+            ),
+            Change::ProfessorChanged { old, new } => format!(
+                "{} {} from {} to {}",
+                name(new),
+                when(new),
+                old.professor,
+                new.professor
+            ),
+            Change::ScheduleChanged { old, new } => format!(
+                "{} {} from {} to {}",
+                name(new),
+                when(new),
+                schedule(old),
+                schedule(new)
+            ),
+        }
+    }
+
+    /// Position of the change in the timetable, to list changes chronologically.
+    fn sort_key(&self) -> (usize, usize) {
+        match self {
+            Change::Removed(block) => (block.day, block.time),
+            _ => self
+                .current()
+                .map(|block| (block.day, block.time))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn name(block: &TimeBlock) -> String {
+    format!("{} ({})", block.subject.name, block.subject.r#type)
+}
+
+fn when(block: &TimeBlock) -> String {
+    format!("{} {}:00", DAYS.get(block.day).unwrap_or(&"?"), block.time)
+}
+
+/// The dates a block takes place on, e.g. `odd weeks, except 10.11.2025`.
+fn schedule(block: &TimeBlock) -> String {
+    let date = |date: &NaiveDate| date.format("%-d.%-m.%Y").to_string();
+    let dates = |dates: &[NaiveDate]| dates.iter().map(date).collect::<Vec<_>>().join(", ");
+    let mut parts = Vec::new();
+    if !block.dates.is_empty() {
+        parts.push(format!("only on {}", dates(&block.dates)));
+    }
+    if let Some(weeks) = block.weeks {
+        parts.push(weeks.label().to_lowercase());
+    }
+    if let Some(from) = &block.valid_from {
+        parts.push(format!("from {}", date(from)));
+    }
+    if let Some(until) = &block.valid_until {
+        parts.push(format!("until {}", date(until)));
+    }
+    if !block.except.is_empty() {
+        parts.push(format!("except {}", dates(&block.except)));
+    }
+    if parts.is_empty() {
+        "every week".into()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn same_schedule(a: &TimeBlock, b: &TimeBlock) -> bool {
+    (a.valid_from, a.valid_until, a.weeks, &a.except, &a.dates)
+        == (b.valid_from, b.valid_until, b.weeks, &b.except, &b.dates)
+}
+
+fn same_subject(a: &TimeBlock, b: &TimeBlock) -> bool {
+    a.subject.name == b.subject.name && a.subject.r#type == b.subject.r#type
+}
+
+/// Pairs each new block with the first old block `same` holds for, removing both from the lists.
+fn take_pairs<'a>(
+    old: &mut Vec<&'a TimeBlock>,
+    new: &mut Vec<&'a TimeBlock>,
+    same: impl Fn(&TimeBlock, &TimeBlock) -> bool,
+) -> Vec<(&'a TimeBlock, &'a TimeBlock)> {
+    let mut pairs = Vec::new();
+    new.retain(|n| match old.iter().position(|o| same(o, n)) {
+        Some(idx) => {
+            pairs.push((old.remove(idx), *n));
+            false
+        }
+        None => true,
+    });
+    pairs
+}
+
+/// The changes between two versions of the same block.
+fn compare(o: &TimeBlock, n: &TimeBlock, changes: &mut Vec<Change>) {
+    let (old, new) = (o.clone(), n.clone());
+    if (o.day, o.time, o.duration) != (n.day, n.time, n.duration) {
+        changes.push(Change::Moved { old, new });
+        return;
+    }
+    if o.classroom != n.classroom {
+        changes.push(Change::RoomChanged {
+            old: old.clone(),
+            new: new.clone(),
+        });
+    }
+    if o.professor != n.professor {
+        changes.push(Change::ProfessorChanged {
+            old: old.clone(),
+            new: new.clone(),
+        });
+    }
+    if !same_schedule(o, n) {
+        changes.push(Change::ScheduleChanged { old, new });
+    }
+}
+
+/// Compares two versions of a timetable.
+///
+/// Blocks are paired up in steps, first identical blocks, then blocks with the same id from the
+/// API, then the same subject and type at the same time, then the same subject and type with the
+/// same room and professor, and at last the same subject and type anywhere. Whatever is left over
+/// was added or removed.
+pub fn diff(old: &[TimeBlock], new: &[TimeBlock]) -> Vec<Change> {
+    let mut old: Vec<&TimeBlock> = old.iter().collect();
+    let mut new: Vec<&TimeBlock> = new.iter().collect();
+    let mut changes = Vec::new();
+
+    take_pairs(&mut old, &mut new, |a, b| a == b);
+    let same_id = |a: &TimeBlock, b: &TimeBlock| a.id.is_some() && a.id == b.id;
+    let mut pairs = take_pairs(&mut old, &mut new, same_id);
+    let same_slot =
+        |a: &TimeBlock, b: &TimeBlock| same_subject(a, b) && a.day == b.day && a.time == b.time;
+    pairs.extend(take_pairs(&mut old, &mut new, same_slot));
+    for (o, n) in pairs {
+        compare(o, n, &mut changes);
+    }
+    let same_people = |a: &TimeBlock, b: &TimeBlock| {
+        same_subject(a, b) && a.classroom == b.classroom && a.professor == b.professor
+    };
+    let mut moved = take_pairs(&mut old, &mut new, same_people);
+    moved.extend(take_pairs(&mut old, &mut new, same_subject));
+    for (o, n) in moved {
+        changes.push(Change::Moved {
+            old: o.clone(),
+            new: n.clone(),
+        });
+    }
+    changes.extend(old.into_iter().cloned().map(Change::Removed));
+    changes.extend(new.into_iter().cloned().map(Change::Added));
+    changes.sort_by_key(|change| change.sort_key());
+    changes
+}
+
+/// Counts of each kind of change, e.g. `1 added, 2 moved`.
+pub fn summary(changes: &[Change]) -> String {
+    let count = |label: &str| {
+        changes
+            .iter()
+            .filter(|change| change.label() == label)
+            .count()
+    };
+    [
+        ("Added", "added"),
+        ("Removed", "removed"),
+        ("Moved", "moved"),
+        ("Room", "changed room"),
+        ("Professor", "changed professor"),
+        ("Dates", "changed dates"),
+    ]
+    .iter()
+    .map(|(label, text)| (count(label), text))
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, text)| format!("{} {}", count, text))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::WeekParity;
+
+    fn labels(changes: &[Change]) -> Vec<&'static str> {
+        changes.iter().map(Change::label).collect()
+    }
+
+    #[test]
+    fn the_same_timetable_has_no_changes() {
+        let blocks = [
+            TimeBlock::sample(0, 8, 2, "Math", "P"),
+            TimeBlock::sample(1, 10, 2, "Physics", "LV"),
+        ];
+        assert!(diff(&blocks, &blocks).is_empty());
+    }
+
+    #[test]
+    fn new_and_dropped_subjects_are_added_and_removed() {
+        let old = [TimeBlock::sample(0, 8, 2, "Math", "P")];
+        let new = [TimeBlock::sample(0, 8, 2, "Physics", "P")];
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                Change::Removed(old[0].clone()),
+                Change::Added(new[0].clone())
+            ]
+        );
+    }
+
+    #[test]
+    fn room_and_professor_changes_at_the_same_time() {
+        let old = TimeBlock::sample(0, 8, 2, "Math", "P");
+        let mut new = old.clone();
+        new.classroom = "P22".into();
+        new.professor = "Someone else".into();
+        let changes = diff(&[old], &[new]);
+        assert_eq!(labels(&changes), ["Room", "Professor"]);
+        assert_eq!(changes[0].describe(), "Math (P) Monday 8:00 from P1 to P22");
+    }
+
+    #[test]
+    fn other_dates_are_a_schedule_change() {
+        let old = TimeBlock::sample(0, 8, 2, "Math", "P");
+        let mut new = old.clone();
+        new.weeks = Some(WeekParity::Odd);
+        new.except = vec![NaiveDate::from_ymd_opt(2025, 11, 10).unwrap()];
+        let changes = diff(&[old], std::slice::from_ref(&new));
+        assert_eq!(labels(&changes), ["Dates"]);
+        assert_eq!(
+            changes[0].describe(),
+            "Math (P) Monday 8:00 from every week to odd weeks, except 10.11.2025"
+        );
+        assert_eq!(summary(&changes), "1 changed dates");
+        // Cancelling one more date is noticed as well
+        let mut cancelled = new.clone();
+        cancelled
+            .except
+            .push(NaiveDate::from_ymd_opt(2025, 11, 17).unwrap());
+        assert_eq!(labels(&diff(&[new], &[cancelled])), ["Dates"]);
+    }
+
+    #[test]
+    fn another_duration_is_a_move() {
+        let old = TimeBlock::sample(0, 8, 2, "Math", "P");
+        let new = TimeBlock::sample(0, 8, 3, "Math", "P");
+        assert_eq!(labels(&diff(&[old], &[new])), ["Moved"]);
+    }
+
+    #[test]
+    fn another_time_is_a_move() {
+        let old = TimeBlock::sample(0, 8, 2, "Math", "P");
+        let mut new = TimeBlock::sample(2, 12, 2, "Math", "P");
+        new.classroom = "P22".into();
+        let changes = diff(&[old], &[new]);
+        assert_eq!(labels(&changes), ["Moved"]);
+        assert_eq!(
+            changes[0].describe(),
+            "Math (P) from Monday 8:00 to Wednesday 12:00, now in P22"
+        );
+    }
+
+    #[test]
+    fn parallel_sessions_are_paired_by_room_and_professor() {
+        let mut first = TimeBlock::sample(0, 8, 2, "Math", "LV");
+        first.classroom = "P1".into();
+        let mut second = TimeBlock::sample(0, 10, 2, "Math", "LV");
+        second.classroom = "P2".into();
+        // Both sessions move, listed the other way around
+        let mut moved_first = first.clone();
+        moved_first.time = 14;
+        let mut moved_second = second.clone();
+        moved_second.time = 12;
+        let changes = diff(
+            &[first.clone(), second.clone()],
+            &[moved_first.clone(), moved_second.clone()],
+        );
+        assert_eq!(
+            changes,
+            [
+                Change::Moved {
+                    old: second,
+                    new: moved_second
+                },
+                Change::Moved {
+                    old: first,
+                    new: moved_first
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_are_listed_by_time() {
+        let old = [TimeBlock::sample(3, 8, 2, "Removed", "P")];
+        let new = [
+            TimeBlock::sample(4, 8, 2, "Friday", "P"),
+            TimeBlock::sample(0, 12, 2, "Monday", "P"),
+        ];
+        let days: Vec<usize> = diff(&old, &new)
+            .iter()
+            .map(|change| change.sort_key().0)
+            .collect();
+        assert_eq!(days, [0, 3, 4]);
+    }
+
+    #[test]
+    fn summary_counts_every_kind() {
+        let old = [
+            TimeBlock::sample(0, 8, 2, "Math", "P"),
+            TimeBlock::sample(1, 8, 2, "Physics", "P"),
+            TimeBlock::sample(2, 8, 2, "Gone", "P"),
+        ];
+        let mut room = old[1].clone();
+        room.classroom = "P22".into();
+        let new = [
+            TimeBlock::sample(0, 10, 2, "Math", "P"),
+            room,
+            TimeBlock::sample(3, 8, 2, "New", "P"),
+            TimeBlock::sample(4, 8, 2, "Also new", "P"),
+        ];
+        assert_eq!(
+            summary(&diff(&old, &new)),
+            "2 added, 1 removed, 1 moved, 1 changed room"
+        );
+        assert_eq!(summary(&[]), "");
+    }
+}
@@ -0,0 +1,395 @@
+use crate::cache::CachedSource;
+use crate::notification::Severity;
+use base64::Engine;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+use ureq::{Agent, Error, Proxy, Timeout};
+
+/// Sent with every request so API operators can tell where the traffic comes from.
+const USER_AGENT: &str = concat!(
+    "tab-term/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/kashregister/tab-term)"
+);
+
+/// How requests to a source are made.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HttpConfig {
+    /// Seconds to wait for the connection to open
+    pub connect_timeout: Option<u64>,
+    /// Seconds to wait for the response and for the timetable to download
+    pub read_timeout: Option<u64>,
+    /// Proxy url such as `http://proxy:3128` or `socks5://proxy:1080`, the `HTTP_PROXY` and
+    /// `HTTPS_PROXY` variables are used if not set
+    pub proxy: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: BTreeMap<String, String>,
+    pub auth: Option<Auth>,
+}
+
+/// Credentials sent in the `Authorization` header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Auth {
+    Bearer { token: Secret },
+    Basic { username: String, password: Secret },
+}
+
+/// Where a credential is read from, so it never has to be written into the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Secret {
+    /// Name of an environment variable
+    Env(String),
+    /// Path of a file holding only the secret
+    File(PathBuf),
+}
+
+impl Secret {
+    /// Reads the secret. Errors name where it was read from, never the value.
+    fn read(&self) -> Result<String, FetchError> {
+        let value = match self {
+            Secret::Env(name) => std::env::var(name).map_err(|_| {
+                credentials_error(&format!("The environment variable {} is not set.", name))
+            })?,
+            Secret::File(path) => std::fs::read_to_string(path).map_err(|err| {
+                credentials_error(&format!("Could not read {}: {}", path.display(), err))
+            })?,
+        };
+        Ok(value.trim().to_string())
+    }
+}
+
+impl Auth {
+    fn header_value(&self) -> Result<String, FetchError> {
+        match self {
+            Auth::Bearer { token } => Ok(format!("Bearer {}", token.read()?)),
+            Auth::Basic { username, password } => {
+                let pair = format!("{}:{}", username, password.read()?);
+                Ok(format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(pair)
+                ))
+            }
+        }
+    }
+}
+
+fn credentials_error(message: &str) -> FetchError {
+    FetchError::new(
+        Severity::Error,
+        "Credentials",
+        message,
+        "Check the auth section of this source in settings.json.",
+    )
+}
+
+/// Why fetching a timetable failed, and what the user can do about it.
+#[derive(Debug, Clone)]
+pub struct FetchError {
+    pub severity: Severity,
+    pub title: String,
+    /// What went wrong
+    pub message: String,
+    /// What the user should try next
+    pub action: String,
+}
+
+impl FetchError {
+    fn new(severity: Severity, title: impl Into<String>, message: &str, action: &str) -> Self {
+        FetchError {
+            severity,
+            title: title.into(),
+            message: message.into(),
+            action: action.into(),
+        }
+    }
+
+    /// The text shown in the popup.
+    pub fn describe(&self) -> String {
+        format!("{}\n\n{}", self.message, self.action)
+    }
+}
+
+fn agent(config: &HttpConfig) -> Result<Agent, FetchError> {
+    let seconds = |secs: Option<u64>| secs.map(Duration::from_secs);
+    let mut builder = Agent::config_builder()
+        .user_agent(USER_AGENT)
+        .timeout_connect(seconds(config.connect_timeout))
+        .timeout_recv_response(seconds(config.read_timeout))
+        .timeout_recv_body(seconds(config.read_timeout));
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Some(Proxy::new(proxy).map_err(classify)?));
+    }
+    Ok(builder.build().into())
+}
+
+/// Result of a successful request.
+#[derive(Debug, Clone)]
+pub enum Fetched {
+    /// The timetable changed, or there was nothing cached to compare with
+    Modified(CachedSource),
+    /// The server answered 304, the cached timetable is still current
+    NotModified,
+}
+
+/// Downloads the timetable from `url`.
+///
+/// With a cached copy, the request is made conditional on its `ETag` and `Last-Modified`, so an
+/// unchanged timetable is not downloaded again.
+pub fn fetch(
+    url: &str,
+    config: &HttpConfig,
+    cached: Option<&CachedSource>,
+) -> Result<Fetched, FetchError> {
+    let mut request = agent(config)?.get(url);
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    if let Some(auth) = &config.auth {
+        request = request.header("Authorization", auth.header_value()?);
+    }
+    if let Some(cached) = cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header("If-Modified-Since", last_modified);
+        }
+    }
+    let mut response = request.call().map_err(classify)?;
+    if response.status() == 304 {
+        return Ok(Fetched::NotModified);
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+    let body = response.body_mut().read_to_string().map_err(classify)?;
+    Ok(Fetched::Modified(CachedSource {
+        url: url.to_string(),
+        etag,
+        last_modified,
+        body,
+    }))
+}
+
+/// Turns every error ureq can return into a message for the user.
+pub fn classify(err: Error) -> FetchError {
+    let check_url = "Check the url in config.txt.";
+    match err {
+        Error::StatusCode(code) => classify_status(code),
+        Error::HostNotFound => host_not_found(),
+        // The system resolver reports failed lookups as plain io errors
+        Error::Io(io) if io.to_string().contains("failed to lookup address") => host_not_found(),
+        Error::Io(io) => match io.kind() {
+            ErrorKind::ConnectionRefused => FetchError::new(
+                Severity::Error,
+                "Connection refused",
+                "The host is reachable but nothing accepts connections on that port.",
+                "Check that the API server is running and the port in the url is right.",
+            ),
+            ErrorKind::TimedOut => FetchError::new(
+                Severity::Warn,
+                "Timed out",
+                "The connection timed out.",
+                "The server or your network may be slow, refresh to try again.",
+            ),
+            ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe => {
+                FetchError::new(
+                    Severity::Warn,
+                    "Connection dropped",
+                    "The server closed the connection before the timetable was received.",
+                    "Refresh to try again.",
+                )
+            }
+            ErrorKind::NetworkUnreachable | ErrorKind::HostUnreachable => FetchError::new(
+                Severity::Error,
+                "Network unreachable",
+                "There is no route to the host.",
+                "Check your internet connection or VPN.",
+            ),
+            _ => FetchError::new(
+                Severity::Error,
+                "Network error",
+                &format!("Reading from the connection failed: {}", io),
+                "Refresh to try again, or check your network.",
+            ),
+        },
+        Error::Timeout(phase) => {
+            let during = match phase {
+                Timeout::Resolve => "while resolving the host name",
+                Timeout::Connect => "while connecting",
+                Timeout::SendRequest | Timeout::SendBody => "while sending the request",
+                Timeout::RecvResponse => "while waiting for the response",
+                Timeout::RecvBody => "while downloading the timetable",
+                _ => "before the request finished",
+            };
+            FetchError::new(
+                Severity::Warn,
+                "Timed out",
+                &format!("The request timed out {}.", during),
+                "The server or your network may be slow, refresh to try again.",
+            )
+        }
+        Error::Tls(_) | Error::Pem(_) | Error::Rustls(_) | Error::TlsRequired => FetchError::new(
+            Severity::Error,
+            "TLS error",
+            &format!("The secure connection could not be established: {}", err),
+            "Check the server certificate, the system clock, or use http:// for local servers.",
+        ),
+        Error::BadUri(uri) => FetchError::new(
+            Severity::Error,
+            "Invalid url",
+            &format!("The url is not valid: {}", uri),
+            "Urls need a scheme and a host, e.g. http://localhost:8080/timetable/fri/61310",
+        ),
+        Error::RequireHttpsOnly(uri) => FetchError::new(
+            Severity::Error,
+            "Https required",
+            &format!("`{}` does not use https.", uri),
+            check_url,
+        ),
+        Error::TooManyRedirects | Error::RedirectFailed => FetchError::new(
+            Severity::Error,
+            "Redirect failed",
+            "The server keeps redirecting the request.",
+            "Use the final url of the API in config.txt.",
+        ),
+        Error::InvalidProxyUrl | Error::ConnectProxyFailed(_) => FetchError::new(
+            Severity::Error,
+            "Proxy error",
+            &format!("The proxy could not be used: {}", err),
+            "Check the proxy of this source in settings.json or the HTTP_PROXY and HTTPS_PROXY \
+            variables.",
+        ),
+        Error::BodyExceedsLimit(limit) => FetchError::new(
+            Severity::Error,
+            "Response too large",
+            &format!("The timetable is larger than the {} byte limit.", limit),
+            &format!(
+                "The url probably does not point to a timetable. {}",
+                check_url
+            ),
+        ),
+        Error::LargeResponseHeader(_, _) => FetchError::new(
+            Severity::Error,
+            "Response too large",
+            "The response headers are too large.",
+            check_url,
+        ),
+        Error::Protocol(_) | Error::Http(_) | Error::Decompress(_, _) => FetchError::new(
+            Severity::Error,
+            "Invalid response",
+            &format!("The server sent an invalid HTTP response: {}", err),
+            "Make sure the url points to the timetable API and not to another service.",
+        ),
+        Error::ConnectionFailed => FetchError::new(
+            Severity::Error,
+            "Connection failed",
+            "No connection to the host could be opened.",
+            "Check the url and your internet connection.",
+        ),
+        _ => FetchError::new(
+            Severity::Error,
+            "Request failed",
+            &format!("The request failed: {}", err),
+            check_url,
+        ),
+    }
+}
+
+fn host_not_found() -> FetchError {
+    FetchError::new(
+        Severity::Error,
+        "Host not found",
+        "The host name of the url could not be resolved.",
+        "Check the url for typos and that you are connected to the internet.",
+    )
+}
+
+fn classify_status(code: u16) -> FetchError {
+    let title = format!("HTTP {}", code);
+    let (severity, message, action) = match code {
+        400 => (
+            Severity::Error,
+            "Bad request: the API did not understand the request.",
+            "Check the url in config.txt.",
+        ),
+        401 => (
+            Severity::Error,
+            "Unauthorized: the API requires authentication.",
+            "Add credentials in the auth section of this source in settings.json.",
+        ),
+        403 => (
+            Severity::Error,
+            "Forbidden: you are not allowed to access this timetable.",
+            "Check the credentials in settings.json, or whether your IP is blocked.",
+        ),
+        404 => (
+            Severity::Error,
+            "Page not found.",
+            "Check the url in config.txt, the timetable id may have changed.",
+        ),
+        408 => (
+            Severity::Warn,
+            "Request timed out...",
+            "The server took too long to answer, refresh to try again.",
+        ),
+        410 => (
+            Severity::Error,
+            "Gone: this timetable no longer exists.",
+            "Look up the new timetable url and update config.txt.",
+        ),
+        429 => (
+            Severity::Warn,
+            "Too many requests...",
+            "The API is rate limiting you, wait a while before refreshing.",
+        ),
+        _ if (400..500).contains(&code) => (
+            Severity::Error,
+            "The API rejected the request.",
+            "Check the url in config.txt.",
+        ),
+        500 => (
+            Severity::Error,
+            "Internal server error: the API failed to build the timetable.",
+            "This is a problem on the server, try again later.",
+        ),
+        502 => (
+            Severity::Warn,
+            "Bad gateway: the server behind the API did not answer properly.",
+            "The scraper or the faculty page may be down, try again later.",
+        ),
+        503 => (
+            Severity::Warn,
+            "Service unavailable: the API is down or overloaded.",
+            "Try again later.",
+        ),
+        504 => (
+            Severity::Warn,
+            "Gateway timeout: the server behind the API took too long.",
+            "Try again later.",
+        ),
+        _ if (500..600).contains(&code) => (
+            Severity::Error,
+            "The API failed to answer the request.",
+            "This is a problem on the server, try again later.",
+        ),
+        _ => (
+            Severity::Error,
+            "The API answered with an unexpected status.",
+            "Check the url in config.txt.",
+        ),
+    };
+    FetchError::new(severity, title, message, action)
+}
@@ -0,0 +1,95 @@
+use crate::app::TimeBlock;
+use serde_derive::{Deserialize, Serialize};
+
+/// Property of a [`TimeBlock`] a filter rule can match on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterField {
+    Subject,
+    Type,
+    Professor,
+    Classroom,
+}
+
+impl FilterField {
+    pub const ALL: [FilterField; 4] = [
+        FilterField::Subject,
+        FilterField::Type,
+        FilterField::Professor,
+        FilterField::Classroom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterField::Subject => "Subject",
+            FilterField::Type => "Type",
+            FilterField::Professor => "Professor",
+            FilterField::Classroom => "Classroom",
+        }
+    }
+
+    pub fn value<'a>(&self, block: &'a TimeBlock) -> &'a str {
+        match self {
+            FilterField::Subject => &block.subject.name,
+            FilterField::Type => &block.subject.r#type,
+            FilterField::Professor => &block.professor,
+            FilterField::Classroom => &block.classroom,
+        }
+    }
+}
+
+/// Hides every block whose `field` equals `value`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub field: FilterField,
+    pub value: String,
+}
+
+impl FilterRule {
+    pub fn matches(&self, block: &TimeBlock) -> bool {
+        self.field.value(block) == self.value
+    }
+}
+
+pub fn is_visible(rules: &[FilterRule], block: &TimeBlock) -> bool {
+    !rules.iter().any(|rule| rule.matches(block))
+}
+
+/// Returns the blocks that are not hidden by any of the rules.
+pub fn apply(rules: &[FilterRule], blocks: &[TimeBlock]) -> Vec<TimeBlock> {
+    blocks
+        .iter()
+        .filter(|block| is_visible(rules, block))
+        .cloned()
+        .collect()
+}
+
+/// Every rule that can be toggled for the given timetable, sorted by field and value.
+///
+/// Rules that are active but no longer match anything are kept, so they can still be removed.
+pub fn entries(rules: &[FilterRule], blocks: &[TimeBlock]) -> Vec<FilterRule> {
+    let mut out: Vec<FilterRule> = rules.to_vec();
+    for block in blocks {
+        for field in FilterField::ALL {
+            let value = field.value(block);
+            if !value.is_empty() {
+                out.push(FilterRule {
+                    field,
+                    value: value.to_string(),
+                });
+            }
+        }
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Adds the rule if it is not active, removes it otherwise.
+pub fn toggle(rules: &mut Vec<FilterRule>, rule: FilterRule) {
+    if let Some(pos) = rules.iter().position(|active| *active == rule) {
+        rules.remove(pos);
+    } else {
+        rules.push(rule);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::app::TimeBlock;
+use crate::ui::{DAYS, FIRST_HOUR, LAST_HOUR};
+
+/// Which free time the grid shows.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FreeTime {
+    #[default]
+    Off,
+    /// Hours without classes in the timetable on screen
+    Own,
+    /// Hours free in every timetable, with the url and last downloaded blocks of each
+    Everyone(Vec<(String, Vec<TimeBlock>)>),
+}
+
+/// Hours of every day without any of the blocks as (from, until), within the hours the grid shows.
+///
+/// Blocks of several timetables can be passed together to find the hours free in all of them.
+pub fn free_hours(blocks: &[TimeBlock]) -> Vec<Vec<(usize, usize)>> {
+    (0..DAYS.len())
+        .map(|day| {
+            let busy = |hour: usize| {
+                blocks.iter().any(|block| {
+                    block.day == day && block.time <= hour && hour < block.time + block.duration
+                })
+            };
+            let mut free: Vec<(usize, usize)> = Vec::new();
+            for hour in (FIRST_HOUR..LAST_HOUR).filter(|hour| !busy(*hour)) {
+                match free.last_mut() {
+                    Some((_, until)) if *until == hour => *until = hour + 1,
+                    _ => free.push((hour, hour + 1)),
+                }
+            }
+            free
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_without_blocks_are_free_all_day() {
+        let free = free_hours(&[TimeBlock::sample(0, 8, 2, "Math", "P")]);
+        assert_eq!(free.len(), DAYS.len());
+        for day in &free[1..] {
+            assert_eq!(day, &[(FIRST_HOUR, LAST_HOUR)]);
+        }
+    }
+
+    #[test]
+    fn free_hours_next_to_each_other_are_merged() {
+        let blocks = [
+            TimeBlock::sample(1, 9, 2, "Math", "P"),
+            TimeBlock::sample(1, 14, 1, "Physics", "P"),
+            // Overlapping the one before, nothing is free in between
+            TimeBlock::sample(1, 15, 2, "Art", "P"),
+            TimeBlock::sample(1, 16, 1, "Music", "P"),
+        ];
+        assert_eq!(
+            free_hours(&blocks)[1],
+            [(FIRST_HOUR, 9), (11, 14), (17, LAST_HOUR)]
+        );
+    }
+
+    #[test]
+    fn timetables_passed_together_share_their_free_time() {
+        let mine = [TimeBlock::sample(2, 8, 2, "Math", "P")];
+        let theirs = [TimeBlock::sample(2, 11, 3, "Physics", "P")];
+        assert_eq!(free_hours(&mine)[2], [(FIRST_HOUR, 8), (10, LAST_HOUR)]);
+        let both: Vec<TimeBlock> = mine.iter().chain(&theirs).cloned().collect();
+        assert_eq!(
+            free_hours(&both)[2],
+            [(FIRST_HOUR, 8), (10, 11), (14, LAST_HOUR)]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::app::TimeBlock;
+use serde_derive::{Deserialize, Serialize};
+
+/// The session a user attends out of several parallel sessions of the same subject and type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupChoice {
+    pub subject: String,
+    pub r#type: String,
+    pub day: usize,
+    pub time: usize,
+}
+
+/// How a block relates to the group choices of its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupStatus {
+    /// No choice was made for this subject and type
+    Unset,
+    /// The session the user attends
+    Chosen,
+    /// A parallel session the user does not attend
+    Other,
+}
+
+impl GroupChoice {
+    pub fn from_block(block: &TimeBlock) -> Self {
+        GroupChoice {
+            subject: block.subject.name.clone(),
+            r#type: block.subject.r#type.clone(),
+            day: block.day,
+            time: block.time,
+        }
+    }
+
+    /// Whether the block is one of the sessions this choice was made between.
+    pub fn same_group(&self, block: &TimeBlock) -> bool {
+        self.subject == block.subject.name && self.r#type == block.subject.r#type
+    }
+
+    pub fn is_chosen(&self, block: &TimeBlock) -> bool {
+        self.same_group(block) && self.day == block.day && self.time == block.time
+    }
+}
+
+pub fn status(choices: &[GroupChoice], block: &TimeBlock) -> GroupStatus {
+    match choices.iter().find(|choice| choice.same_group(block)) {
+        None => GroupStatus::Unset,
+        Some(choice) if choice.is_chosen(block) => GroupStatus::Chosen,
+        Some(_) => GroupStatus::Other,
+    }
+}
+
+/// Whether the block's subject and type also take place at another day or time.
+pub fn has_parallel(blocks: &[TimeBlock], block: &TimeBlock) -> bool {
+    let choice = GroupChoice::from_block(block);
+    blocks
+        .iter()
+        .any(|other| choice.same_group(other) && !choice.is_chosen(other))
+}
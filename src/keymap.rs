@@ -0,0 +1,528 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Everything a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    Quit,
+    Refresh,
+    Up,
+    Down,
+    Left,
+    Right,
+    Details,
+    NextWeek,
+    PreviousWeek,
+    NextSource,
+    PreviousSource,
+    Filters,
+    ToggleGroup,
+    ToggleOtherGroups,
+    MuteSubject,
+    FreeTime,
+    Note,
+    SubjectNote,
+    Search,
+    Help,
+    Messages,
+    Changes,
+    Problems,
+    Statistics,
+    Events,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    Close,
+    Toggle,
+    New,
+    Edit,
+    Delete,
+    Confirm,
+    Cancel,
+    DeleteChar,
+    NextField,
+    PreviousField,
+}
+
+impl Action {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Refresh => "Refresh the timetable",
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Previous day",
+            Action::Right => "Next day",
+            Action::Details => "Show details of the selected block",
+            Action::NextWeek => "Next week",
+            Action::PreviousWeek => "Previous week",
+            Action::NextSource => "Next timetable",
+            Action::PreviousSource => "Previous timetable",
+            Action::Filters => "Filter subjects, types, professors and classrooms",
+            Action::ToggleGroup => "Mark the selected block as your group",
+            Action::ToggleOtherGroups => "Hide or dim parallel sessions you did not pick",
+            Action::MuteSubject => "Mute or unmute reminders for the selected subject",
+            Action::FreeTime => "Show free time of this timetable, of every timetable or none",
+            Action::Note => "Write a note on the selected block",
+            Action::SubjectNote => "Write a note on the subject of the selected block",
+            Action::Search => "Search",
+            Action::Help => "Show this help",
+            Action::Messages => "Show the message log",
+            Action::Changes => "Show what changed in the last refresh",
+            Action::Problems => "Show blocks of the timetable that could not be shown",
+            Action::Statistics => "Show the hours of classes in the week shown",
+            Action::Events => "Add, edit and delete your own events",
+            Action::NextMatch => "Next search match",
+            Action::PreviousMatch => "Previous search match",
+            Action::ClearSearch => "Clear the search",
+            Action::Close => "Close the popup",
+            Action::Toggle => "Toggle the entry",
+            Action::New => "Add an entry",
+            Action::Edit => "Edit the entry",
+            Action::Delete => "Delete the entry",
+            Action::Confirm => "Confirm",
+            Action::Cancel => "Cancel",
+            Action::DeleteChar => "Delete a character",
+            Action::NextField => "Next field",
+            Action::PreviousField => "Previous field",
+        }
+    }
+}
+
+/// Where a binding applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Context {
+    /// The timetable grid
+    Grid,
+    /// Any popup drawn on top of the grid
+    Popup,
+    /// Typing a search query
+    Search,
+    /// Typing into the fields of a form
+    Input,
+}
+
+impl Context {
+    pub const ALL: [Context; 4] = [
+        Context::Grid,
+        Context::Popup,
+        Context::Search,
+        Context::Input,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Context::Grid => "Grid",
+            Context::Popup => "Popup",
+            Context::Search => "Search",
+            Context::Input => "Text input",
+        }
+    }
+}
+
+/// A key together with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parses bindings such as `q`, `G`, `ctrl+c`, `shift+tab`, `esc` or `pagedown`.
+    pub fn parse(text: &str) -> Result<KeyBinding, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // A binding of "+" itself splits into two empty parts
+        let key = if text.ends_with("++") || text == "+" {
+            parts.truncate(parts.len().saturating_sub(2));
+            "+"
+        } else {
+            parts.pop().unwrap_or_default()
+        };
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier `{}` in `{}`", modifier, text)),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                lower => match lower.strip_prefix('f').map(str::parse::<u8>) {
+                    Some(Ok(n)) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key `{}`", text)),
+                },
+            },
+        };
+        Ok(KeyBinding { code, modifiers }.normalized())
+    }
+
+    /// Drops the shift modifier where it is already part of the key, so `G` and `shift+g` or
+    /// `shift+tab` and `backtab` are the same binding.
+    fn normalized(mut self) -> KeyBinding {
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            match self.code {
+                KeyCode::Char(c) => {
+                    self.code = KeyCode::Char(c.to_ascii_uppercase());
+                    self.modifiers.remove(KeyModifiers::SHIFT);
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.code = KeyCode::BackTab;
+                    self.modifiers.remove(KeyModifiers::SHIFT);
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        KeyBinding {
+            code: event.code,
+            modifiers: event.modifiers,
+        }
+        .normalized()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Built-in sets of bindings the user configuration starts from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Preset {
+    /// Both arrow keys and vim keys
+    #[default]
+    Default,
+    /// Only hjkl for movement
+    Vim,
+    /// Only the arrow keys for movement
+    Arrows,
+}
+
+impl Preset {
+    fn bindings(&self) -> Vec<(Context, &'static str, Action)> {
+        use Action::*;
+        use Context::{Grid, Input, Popup};
+        let mut bindings = vec![
+            (Grid, "q", Quit),
+            (Grid, "r", Refresh),
+            (Grid, "enter", Details),
+            (Grid, "]", NextWeek),
+            (Grid, "[", PreviousWeek),
+            (Grid, "tab", NextSource),
+            (Grid, "shift+tab", PreviousSource),
+            (Grid, "f", Filters),
+            (Grid, "g", ToggleGroup),
+            (Grid, "G", ToggleOtherGroups),
+            (Grid, "m", MuteSubject),
+            (Grid, "F", FreeTime),
+            (Grid, "a", Note),
+            (Grid, "A", SubjectNote),
+            (Grid, "/", Action::Search),
+            (Grid, "n", NextMatch),
+            (Grid, "N", PreviousMatch),
+            (Grid, "esc", ClearSearch),
+            (Grid, "?", Help),
+            (Grid, "L", Messages),
+            (Grid, "c", Changes),
+            (Grid, "v", Problems),
+            (Grid, "s", Statistics),
+            (Grid, "e", Events),
+            (Popup, "q", Quit),
+            (Popup, "esc", Close),
+            (Popup, "space", Toggle),
+            (Popup, "enter", Toggle),
+            (Popup, "a", New),
+            (Popup, "e", Edit),
+            (Popup, "d", Delete),
+            (Context::Search, "enter", Confirm),
+            (Context::Search, "esc", Cancel),
+            (Context::Search, "backspace", DeleteChar),
+            (Input, "enter", Confirm),
+            (Input, "esc", Cancel),
+            (Input, "backspace", DeleteChar),
+            (Input, "tab", NextField),
+            (Input, "down", NextField),
+            (Input, "shift+tab", PreviousField),
+            (Input, "up", PreviousField),
+        ];
+        let vim = [
+            (Grid, "k", Up),
+            (Grid, "j", Down),
+            (Grid, "h", Left),
+            (Grid, "l", Right),
+            (Popup, "k", Up),
+            (Popup, "j", Down),
+        ];
+        let arrows = [
+            (Grid, "up", Up),
+            (Grid, "down", Down),
+            (Grid, "left", Left),
+            (Grid, "right", Right),
+            (Popup, "up", Up),
+            (Popup, "down", Down),
+        ];
+        match self {
+            Preset::Default => {
+                bindings.extend(vim);
+                bindings.extend(arrows);
+            }
+            Preset::Vim => bindings.extend(vim),
+            Preset::Arrows => bindings.extend(arrows),
+        }
+        bindings
+    }
+}
+
+/// The `keymap` section of the settings.
+///
+/// Each context maps an action to the keys that trigger it, replacing the preset's keys for
+/// that action.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct KeymapConfig {
+    pub preset: Preset,
+    pub grid: BTreeMap<Action, Vec<String>>,
+    pub popup: BTreeMap<Action, Vec<String>>,
+    pub search: BTreeMap<Action, Vec<String>>,
+    pub input: BTreeMap<Action, Vec<String>>,
+}
+
+/// The active key bindings.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Context, KeyBinding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::load(&KeymapConfig::default()).0
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from the configuration.
+    ///
+    /// Also returns every problem found: keys that could not be parsed and keys bound to more
+    /// than one action in the same context. On a conflict the first binding wins.
+    pub fn load(config: &KeymapConfig) -> (Keymap, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut bindings: Vec<(Context, KeyBinding, Action)> = config
+            .preset
+            .bindings()
+            .into_iter()
+            .map(|(context, key, action)| {
+                let key = KeyBinding::parse(key).expect("preset keys are valid");
+                (context, key, action)
+            })
+            .collect();
+        for (context, overrides) in [
+            (Context::Grid, &config.grid),
+            (Context::Popup, &config.popup),
+            (Context::Search, &config.search),
+            (Context::Input, &config.input),
+        ] {
+            for (action, keys) in overrides {
+                bindings.retain(|(c, _, a)| !(*c == context && a == action));
+                for key in keys {
+                    match KeyBinding::parse(key) {
+                        Ok(key) => bindings.push((context, key, *action)),
+                        Err(err) => problems.push(err),
+                    }
+                }
+            }
+        }
+
+        for (i, (context, key, action)) in bindings.iter().enumerate() {
+            let first = bindings
+                .iter()
+                .position(|(c, k, _)| c == context && k == key);
+            if let Some(first) = first.filter(|first| *first < i) {
+                let other = bindings[first].2;
+                if other != *action {
+                    problems.push(format!(
+                        "`{}` is bound to both {:?} and {:?} in the {} context",
+                        key,
+                        other,
+                        action,
+                        context.label()
+                    ));
+                }
+            }
+        }
+        (Keymap { bindings }, problems)
+    }
+
+    pub fn action(&self, context: Context, key: KeyBinding) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(c, k, _)| *c == context && *k == key)
+            .map(|(_, _, action)| *action)
+    }
+
+    /// The actions of a context in a stable order, each with all of its keys.
+    pub fn actions(&self, context: Context) -> Vec<(Action, Vec<KeyBinding>)> {
+        let mut out: Vec<(Action, Vec<KeyBinding>)> = Vec::new();
+        for (c, key, action) in &self.bindings {
+            if *c != context {
+                continue;
+            }
+            match out.iter_mut().find(|(a, _)| a == action) {
+                Some((_, keys)) => keys.push(*key),
+                None => out.push((*action, vec![*key])),
+            }
+        }
+        out.sort_by_key(|(action, _)| *action);
+        out
+    }
+
+    /// The first key bound to the action, for hints.
+    pub fn key_hint(&self, context: Context, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(c, _, a)| *c == context && *a == action)
+            .map(|(_, key, _)| key.to_string())
+            .unwrap_or_else(|| "unbound".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    #[test]
+    fn keys_are_parsed_with_their_modifiers() {
+        let none = KeyModifiers::NONE;
+        assert_eq!(KeyBinding::parse("q"), Ok(key(KeyCode::Char('q'), none)));
+        assert_eq!(KeyBinding::parse("Esc"), Ok(key(KeyCode::Esc, none)));
+        assert_eq!(
+            KeyBinding::parse("space"),
+            Ok(key(KeyCode::Char(' '), none))
+        );
+        assert_eq!(KeyBinding::parse("f5"), Ok(key(KeyCode::F(5), none)));
+        assert_eq!(
+            KeyBinding::parse("ctrl+alt+c"),
+            Ok(key(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(KeyBinding::parse("+"), Ok(key(KeyCode::Char('+'), none)));
+        assert_eq!(
+            KeyBinding::parse("ctrl++"),
+            Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn shift_is_folded_into_the_key() {
+        assert_eq!(KeyBinding::parse("shift+g"), KeyBinding::parse("G"));
+        assert_eq!(KeyBinding::parse("shift+tab"), KeyBinding::parse("backtab"));
+        let event = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(Ok(KeyBinding::from(event)), KeyBinding::parse("G"));
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_are_errors() {
+        assert_eq!(
+            KeyBinding::parse("hyper+x"),
+            Err("Unknown modifier `hyper` in `hyper+x`".into())
+        );
+        assert_eq!(KeyBinding::parse("f13"), Err("Unknown key `f13`".into()));
+        assert_eq!(KeyBinding::parse("qq"), Err("Unknown key `qq`".into()));
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in [Preset::Default, Preset::Vim, Preset::Arrows] {
+            let config = KeymapConfig {
+                preset,
+                ..KeymapConfig::default()
+            };
+            let (_, problems) = Keymap::load(&config);
+            assert!(problems.is_empty(), "{:?}: {:?}", preset, problems);
+        }
+    }
+
+    #[test]
+    fn overrides_replace_the_preset_keys() {
+        let mut config = KeymapConfig::default();
+        config
+            .grid
+            .insert(Action::Quit, vec!["ctrl+q".into(), "x".into()]);
+        let (keymap, problems) = Keymap::load(&config);
+        assert!(problems.is_empty(), "{:?}", problems);
+        let grid = |text| keymap.action(Context::Grid, KeyBinding::parse(text).unwrap());
+        assert_eq!(grid("q"), None);
+        assert_eq!(grid("ctrl+q"), Some(Action::Quit));
+        assert_eq!(grid("x"), Some(Action::Quit));
+        // Other contexts keep their keys
+        let popup = keymap.action(Context::Popup, KeyBinding::parse("q").unwrap());
+        assert_eq!(popup, Some(Action::Quit));
+    }
+
+    #[test]
+    fn conflicts_are_reported_and_the_first_binding_wins() {
+        let mut config = KeymapConfig::default();
+        config.grid.insert(Action::Quit, vec!["r".into()]);
+        config.grid.insert(Action::Help, vec!["nope+?".into()]);
+        let (keymap, problems) = Keymap::load(&config);
+        assert_eq!(
+            problems,
+            [
+                "Unknown modifier `nope` in `nope+?`",
+                "`r` is bound to both Refresh and Quit in the Grid context",
+            ]
+        );
+        let r = KeyBinding::parse("r").unwrap();
+        assert_eq!(keymap.action(Context::Grid, r), Some(Action::Refresh));
+    }
+}
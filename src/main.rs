@@ -1,14 +1,43 @@
 use crate::app::App;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 
 pub mod app;
+pub mod cache;
+pub mod calendar;
+pub mod config;
+pub mod diff;
 pub mod event;
+pub mod fetch;
+pub mod filter;
+pub mod free;
+pub mod groups;
+pub mod keymap;
+pub mod notes;
+pub mod notification;
+pub mod palette;
+pub mod personal;
+pub mod reminder;
+pub mod schema;
+pub mod search;
+pub mod stats;
+pub mod theme;
 pub mod ui;
+pub mod validate;
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     let terminal = ratatui::init();
+    // The hook installed by ratatui restores the terminal, mouse capture is turned off before it
+    let restore_terminal = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+        restore_terminal(info);
+    }));
+    // Without mouse capture the app still works from the keyboard, so errors are ignored
+    let _ = crossterm::execute!(std::io::stdout(), EnableMouseCapture);
     let result = App::default().run(terminal).await;
+    let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
     ratatui::restore();
     result
 }
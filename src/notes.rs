@@ -0,0 +1,168 @@
+use crate::app::TimeBlock;
+use crate::config;
+use crate::schema::BlockId;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// Free text and tags the user attached to a subject or a block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Note {
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+impl Note {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.tags.is_empty()
+    }
+
+    /// The tags as typed in the editor, e.g. `#exam #laptop`.
+    pub fn tag_line(&self) -> String {
+        let tags: Vec<String> = self.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        tags.join(" ")
+    }
+}
+
+/// What a note is attached to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteTarget {
+    /// Every block of a subject, by name
+    Subject(String),
+    /// A single block
+    Block(BlockId),
+}
+
+/// Every note, stored as `notes.json` in the data directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notes {
+    pub subjects: BTreeMap<String, Note>,
+    pub blocks: BTreeMap<BlockId, Note>,
+}
+
+impl Notes {
+    pub fn path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join("notes.json"))
+    }
+
+    /// Reads the saved notes, none if the file does not exist yet.
+    pub fn load() -> Result<Notes, String> {
+        config::read_json(Notes::path(), "notes").map(Option::unwrap_or_default)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = Notes::path() else {
+            return Err("No data directory available".into());
+        };
+        config::write_json(&path, self)
+    }
+
+    pub fn get(&self, target: &NoteTarget) -> Option<&Note> {
+        match target {
+            NoteTarget::Subject(name) => self.subjects.get(name),
+            NoteTarget::Block(key) => self.blocks.get(key),
+        }
+    }
+
+    /// Replaces the note of the target, removing it if it is empty.
+    pub fn set(&mut self, target: NoteTarget, note: Note) {
+        match (target, note.is_empty()) {
+            (NoteTarget::Subject(name), true) => self.subjects.remove(&name),
+            (NoteTarget::Subject(name), false) => self.subjects.insert(name, note),
+            (NoteTarget::Block(id), true) => self.blocks.remove(&id),
+            (NoteTarget::Block(id), false) => self.blocks.insert(id, note),
+        };
+    }
+
+    /// Moves the notes of blocks that got an id from the API since they were written, returns
+    /// whether any moved.
+    pub fn migrate(&mut self, source: &str, blocks: &[TimeBlock]) -> bool {
+        let mut moved = false;
+        for block in blocks.iter().filter(|block| block.id.is_some()) {
+            if let Some(note) = self.blocks.remove(&BlockId::derived(source, block)) {
+                self.blocks
+                    .entry(BlockId::new(source, block))
+                    .or_insert(note);
+                moved = true;
+            }
+        }
+        moved
+    }
+}
+
+/// The text typed into the note editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteForm {
+    pub target: NoteTarget,
+    /// What the note is attached to, for the title of the editor
+    pub label: String,
+    /// Field the cursor is in, indexes [`NoteForm::LABELS`]
+    pub field: usize,
+    pub values: [String; 2],
+}
+
+impl NoteForm {
+    pub const LABELS: [&'static str; 2] = ["Note", "Tags"];
+
+    pub fn new(target: NoteTarget, label: String, note: Option<&Note>) -> Self {
+        NoteForm {
+            target,
+            label,
+            field: 0,
+            values: [
+                note.map(|note| note.text.clone()).unwrap_or_default(),
+                note.map(Note::tag_line).unwrap_or_default(),
+            ],
+        }
+    }
+
+    /// The note as typed, tags are separated by spaces or commas and may start with `#`.
+    pub fn note(&self) -> Note {
+        let mut tags: Vec<String> = self.values[1]
+            .split([' ', ','])
+            .map(|tag| tag.trim_start_matches('#'))
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| tag.to_string())
+            .collect();
+        let mut seen = HashSet::new();
+        tags.retain(|tag| seen.insert(tag.clone()));
+        Note {
+            text: self.values[0].trim().to_string(),
+            tags,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str, tags: &str) -> Note {
+        let mut form = NoteForm::new(NoteTarget::Subject("Math".into()), "Math".into(), None);
+        form.values = [text.into(), tags.into()];
+        form.note()
+    }
+
+    #[test]
+    fn tags_are_split_by_spaces_and_commas() {
+        let note = typed(" Bring a laptop ", "#exam, laptop,,#group  work");
+        assert_eq!(note.text, "Bring a laptop");
+        assert_eq!(note.tags, ["exam", "laptop", "group", "work"]);
+        assert_eq!(note.tag_line(), "#exam #laptop #group #work");
+    }
+
+    #[test]
+    fn repeated_tags_are_kept_once_in_the_typed_order() {
+        assert_eq!(
+            typed("", "#exam #laptop #exam laptop").tags,
+            ["exam", "laptop"]
+        );
+    }
+
+    #[test]
+    fn a_lone_hash_is_not_a_tag() {
+        assert!(typed("  ", "# ,").is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long an info toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// How many notifications the message log keeps.
+const LOG_LIMIT: usize = 200;
+
+/// How serious a [`Notification`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Shown as a toast that disappears on its own
+    Info,
+    /// Shown in a popup until it is closed
+    Warn,
+    /// Shown in a popup until it is closed
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub severity: Severity,
+    pub title: String,
+    pub message: String,
+    /// When it was raised, for the message log
+    pub at: DateTime<Local>,
+}
+
+impl Notification {
+    pub fn new(severity: Severity, title: impl Into<String>, message: impl Into<String>) -> Self {
+        Notification {
+            severity,
+            title: title.into(),
+            message: message.into(),
+            at: Local::now(),
+        }
+    }
+}
+
+/// Every notification of the app: the popups waiting to be closed, the toasts on screen and
+/// the log of everything raised so far.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    /// Warnings and errors waiting to be closed, oldest first
+    pub pending: VecDeque<Notification>,
+    /// Info messages on screen, with the time they disappear
+    pub toasts: Vec<(Notification, Instant)>,
+    /// The last [`LOG_LIMIT`] notifications, oldest first
+    pub log: Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, notification: Notification) {
+        self.log.push(notification.clone());
+        if self.log.len() > LOG_LIMIT {
+            self.log.drain(..self.log.len() - LOG_LIMIT);
+        }
+        match notification.severity {
+            Severity::Info => self
+                .toasts
+                .push((notification, Instant::now() + TOAST_DURATION)),
+            Severity::Warn | Severity::Error => self.pending.push_back(notification),
+        }
+    }
+
+    /// The popup shown on top of everything else.
+    pub fn current(&self) -> Option<&Notification> {
+        self.pending.front()
+    }
+
+    /// Closes the current popup, showing the next one if there is any.
+    pub fn dismiss(&mut self) {
+        self.pending.pop_front();
+    }
+
+    /// Removes the toasts whose time is up.
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts.retain(|(_, until)| *until > now);
+    }
+}
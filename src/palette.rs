@@ -0,0 +1,210 @@
+use ratatui::style::Color;
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// No colors at all, as requested through `NO_COLOR`
+    NoColor,
+}
+
+/// The `colorMode` setting, `auto` detects the mode from the environment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorModeSetting {
+    #[default]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    #[serde(rename = "none")]
+    NoColor,
+}
+
+/// RGB values of the 16 ANSI colors as xterm draws them.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube in the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorMode {
+    pub fn from_setting(setting: ColorModeSetting) -> ColorMode {
+        match setting {
+            ColorModeSetting::Auto => ColorMode::detect(),
+            ColorModeSetting::TrueColor => ColorMode::TrueColor,
+            ColorModeSetting::Ansi256 => ColorMode::Ansi256,
+            ColorModeSetting::Ansi16 => ColorMode::Ansi16,
+            ColorModeSetting::NoColor => ColorMode::NoColor,
+        }
+    }
+
+    /// Detects the color support from `NO_COLOR`, `COLORTERM`, `TERM` and the terminfo entry.
+    pub fn detect() -> ColorMode {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            return ColorMode::NoColor;
+        }
+        if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            return ColorMode::TrueColor;
+        }
+        let term = var("TERM");
+        if term == "dumb" {
+            return ColorMode::NoColor;
+        }
+        if term.ends_with("-direct") {
+            return ColorMode::TrueColor;
+        }
+        match terminfo_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => ColorMode::TrueColor,
+            Some(colors) if colors >= 256 => ColorMode::Ansi256,
+            Some(colors) if colors < 8 => ColorMode::NoColor,
+            Some(_) => ColorMode::Ansi16,
+            None if term.contains("256color") => ColorMode::Ansi256,
+            None => ColorMode::Ansi16,
+        }
+    }
+
+    /// Maps a color to the closest one the terminal can show.
+    pub fn map(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorMode::NoColor, _) => Color::Reset,
+            (ColorMode::TrueColor, _) => color,
+            (ColorMode::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+            (ColorMode::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+            (ColorMode::Ansi16, Color::Indexed(idx)) => nearest_16(indexed_rgb(idx)),
+            _ => color,
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// Closest entry of the color cube or the grayscale ramp of the 256 color palette.
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (channel as i32 - **level as i32).abs())
+            .map(|(idx, _)| idx as u8)
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = ((average.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray;
+
+    if distance(rgb, (gray_level, gray_level, gray_level)) < distance(rgb, indexed_rgb(cube)) {
+        232 + gray
+    } else {
+        cube
+    }
+}
+
+fn indexed_rgb(idx: u8) -> (u8, u8, u8) {
+    match idx {
+        0..16 => ANSI16[idx as usize].1,
+        16..232 => {
+            let idx = idx - 16;
+            (
+                CUBE_LEVELS[(idx / 36) as usize],
+                CUBE_LEVELS[(idx / 6 % 6) as usize],
+                CUBE_LEVELS[(idx % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (idx - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Reads the `colors` capability of the compiled terminfo entry for `term`.
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(dir) = std::env::var("TERMINFO") {
+        dirs.push(dir.into());
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".terminfo"));
+    }
+    for dir in ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"] {
+        dirs.push(dir.into());
+    }
+    let data = dirs.iter().find_map(|dir| {
+        // Entries live in a directory named after the first letter, or its hex code on macOS
+        std::fs::read(dir.join(first.to_string()).join(term))
+            .or_else(|_| std::fs::read(dir.join(format!("{:x}", first as u32)).join(term)))
+            .ok()
+    })?;
+    parse_terminfo_colors(&data)
+}
+
+/// Index of `colors` among the numeric capabilities.
+const TERMINFO_COLORS: usize = 13;
+
+fn parse_terminfo_colors(data: &[u8]) -> Option<u32> {
+    let short = |at: usize| -> Option<usize> {
+        Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]) as usize)
+    };
+    // The extended format stores numbers as 32 bit instead of 16 bit integers
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let names_size = short(2)?;
+    let bools = short(4)?;
+    let numbers = short(6)?;
+    if TERMINFO_COLORS >= numbers {
+        return None;
+    }
+    let mut at = 12 + names_size + bools;
+    // Numbers start on an even byte
+    at += at % 2;
+    at += TERMINFO_COLORS * number_size;
+    let bytes = data.get(at..at + number_size)?;
+    let value = match number_size {
+        2 => i16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+    u32::try_from(value).ok()
+}
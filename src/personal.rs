@@ -0,0 +1,133 @@
+use crate::app::{Origin, Subject, TimeBlock};
+use crate::config;
+use crate::ui::{DAYS, FIRST_HOUR, LAST_HOUR};
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// An entry of the user's own, such as a study group or office hours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalEvent {
+    pub name: String,
+    pub day: usize,
+    pub time: usize,
+    pub duration: usize,
+    #[serde(default)]
+    pub location: String,
+}
+
+impl PersonalEvent {
+    /// The event as a block of the grid.
+    pub fn to_block(&self) -> TimeBlock {
+        TimeBlock {
+            id: None,
+            day: self.day,
+            time: self.time,
+            duration: self.duration,
+            professor: String::new(),
+            classroom: self.location.clone(),
+            subject: Subject {
+                name: self.name.clone(),
+                abbreviation: self.name.chars().take(3).collect(),
+                location: String::new(),
+                r#type: "Personal".into(),
+            },
+            valid_from: None,
+            valid_until: None,
+            weeks: None,
+            except: Vec::new(),
+            dates: Vec::new(),
+            origin: Origin::Personal,
+        }
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("events.json"))
+}
+
+/// Reads the saved events, none if the file does not exist yet.
+pub fn load() -> Result<Vec<PersonalEvent>, String> {
+    config::read_json(path(), "events").map(Option::unwrap_or_default)
+}
+
+pub fn save(events: &[PersonalEvent]) -> Result<(), String> {
+    let Some(path) = path() else {
+        return Err("No data directory available".into());
+    };
+    config::write_json(&path, &events)
+}
+
+/// The text typed into the event form, one value per field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventForm {
+    /// Index of the event being edited, `None` for a new event
+    pub editing: Option<usize>,
+    /// Field the cursor is in, indexes [`EventForm::LABELS`]
+    pub field: usize,
+    pub values: [String; 5],
+}
+
+impl EventForm {
+    pub const LABELS: [&'static str; 5] = ["Name", "Day", "Start hour", "Duration", "Location"];
+
+    pub fn edit(idx: usize, event: &PersonalEvent) -> Self {
+        EventForm {
+            editing: Some(idx),
+            field: 0,
+            values: [
+                event.name.clone(),
+                DAYS.get(event.day).unwrap_or(&"").to_string(),
+                event.time.to_string(),
+                event.duration.to_string(),
+                event.location.clone(),
+            ],
+        }
+    }
+
+    /// Checks the typed values and builds the event from them.
+    ///
+    /// Days are given by name, `mon` is enough, or as a number from 1 to 5.
+    pub fn parse(&self) -> Result<PersonalEvent, String> {
+        let [name, day, time, duration, location] = self.values.each_ref().map(|v| v.trim());
+        if name.is_empty() {
+            return Err("The event needs a name".into());
+        }
+        let day = match day.parse::<usize>() {
+            Ok(n) if (1..=DAYS.len()).contains(&n) => n - 1,
+            _ => DAYS
+                .iter()
+                .position(|d| day.len() >= 2 && d.to_lowercase().starts_with(&day.to_lowercase()))
+                .ok_or_else(|| format!("`{}` is not a day from Monday to Friday", day))?,
+        };
+        let time = time
+            .parse::<usize>()
+            .ok()
+            .filter(|time| (FIRST_HOUR..LAST_HOUR).contains(time))
+            .ok_or_else(|| {
+                format!(
+                    "The start hour `{}` is not between {} and {}",
+                    time,
+                    FIRST_HOUR,
+                    LAST_HOUR - 1
+                )
+            })?;
+        let duration = duration
+            .parse::<usize>()
+            .ok()
+            .filter(|duration| *duration >= 1 && time + duration <= LAST_HOUR)
+            .ok_or_else(|| {
+                format!(
+                    "The event has to last at least an hour and end by {}:00",
+                    LAST_HOUR
+                )
+            })?;
+        Ok(PersonalEvent {
+            name: name.to_string(),
+            day,
+            time,
+            duration,
+            location: location.to_string(),
+        })
+    }
+}
@@ -0,0 +1,318 @@
+use crate::app::TimeBlock;
+use crate::calendar::{self, Holiday};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Shows a reminder outside of the terminal.
+pub trait Notifier: std::fmt::Debug {
+    fn send(&mut self, summary: &str, body: &str) -> Result<(), String>;
+}
+
+/// Sends reminders to the desktop through the freedesktop notification interface on D-Bus.
+#[derive(Debug, Default)]
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn send(&mut self, summary: &str, body: &str) -> Result<(), String> {
+        notify_rust::Notification::new()
+            .appname("tab-term")
+            .summary(summary)
+            .body(body)
+            .show()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// The `reminders` section of the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReminderConfig {
+    /// Send desktop notifications before classes
+    pub enabled: bool,
+    /// How many minutes before a class starts the reminder is sent
+    pub lead_minutes: u32,
+    /// Subjects no reminders are sent for
+    pub muted: Vec<String>,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        ReminderConfig {
+            enabled: false,
+            lead_minutes: 10,
+            muted: Vec::new(),
+        }
+    }
+}
+
+/// The `alert` section of the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AlertConfig {
+    /// Flash the grid and show a banner before classes
+    pub enabled: bool,
+    /// How many minutes before a class starts the alert is shown
+    pub lead_minutes: u32,
+    /// Also ring the terminal bell once per class
+    pub bell: bool,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        AlertConfig {
+            enabled: false,
+            lead_minutes: 5,
+            bell: false,
+        }
+    }
+}
+
+/// The earliest class starting within `lead_minutes` that is not muted, with the minutes left.
+pub fn next_class(
+    blocks: &[TimeBlock],
+    muted: &[String],
+    lead_minutes: u32,
+    now: DateTime<Local>,
+) -> Option<(TimeBlock, i64)> {
+    blocks
+        .iter()
+        .filter(|block| !muted.contains(&block.subject.name))
+        .filter_map(|block| minutes_until(block, now).map(|minutes| (block, minutes)))
+        .filter(|(_, minutes)| *minutes <= lead_minutes as i64)
+        .min_by_key(|(block, minutes)| (*minutes, &block.subject.name))
+        .map(|(block, minutes)| (block.clone(), minutes))
+}
+
+/// Minutes until the block starts, if it takes place later today.
+pub fn minutes_until(block: &TimeBlock, now: DateTime<Local>) -> Option<i64> {
+    if block.day != now.weekday().num_days_from_monday() as usize {
+        return None;
+    }
+    let minutes = block.time as i64 * 60 - (now.hour() * 60 + now.minute()) as i64;
+    (minutes > 0).then_some(minutes)
+}
+
+/// Sends a desktop notification once for every class that is about to start.
+#[derive(Debug)]
+pub struct Reminders {
+    notifier: Box<dyn Notifier>,
+    /// Classes already reminded of, by date, time and subject
+    sent: HashSet<(NaiveDate, usize, String)>,
+    /// The minute the blocks were last checked in, reminders are only due once a minute
+    checked: Option<(NaiveDate, u32)>,
+    /// Whether sending failed before, so the error is only reported once
+    failed: bool,
+}
+
+impl Default for Reminders {
+    fn default() -> Self {
+        Reminders::new(Box::new(DesktopNotifier))
+    }
+}
+
+impl Reminders {
+    pub fn new(notifier: Box<dyn Notifier>) -> Self {
+        Reminders {
+            notifier,
+            sent: HashSet::new(),
+            checked: None,
+            failed: false,
+        }
+    }
+
+    /// Whether a new minute started since the last check.
+    pub fn due(&mut self, now: DateTime<Local>) -> bool {
+        let minute = (now.date_naive(), now.hour() * 60 + now.minute());
+        if self.checked == Some(minute) {
+            return false;
+        }
+        self.checked = Some(minute);
+        self.sent.retain(|(date, _, _)| *date == minute.0);
+        true
+    }
+
+    /// Reminds of every block starting within the lead time that was not reminded of yet, unless
+    /// today is a holiday.
+    ///
+    /// Returns the error of the first reminder that could not be sent.
+    pub fn check(
+        &mut self,
+        config: &ReminderConfig,
+        blocks: &[TimeBlock],
+        holidays: &[Holiday],
+        now: DateTime<Local>,
+    ) -> Option<String> {
+        if calendar::holiday_on(holidays, now.date_naive()).is_some() {
+            return None;
+        }
+        let mut error = None;
+        for block in blocks {
+            let Some(minutes) = minutes_until(block, now) else {
+                continue;
+            };
+            if minutes > config.lead_minutes as i64 || config.muted.contains(&block.subject.name) {
+                continue;
+            }
+            let key = (now.date_naive(), block.time, block.subject.name.clone());
+            if !self.sent.insert(key) {
+                continue;
+            }
+            let summary = format!(
+                "{} in {} minute{}",
+                block.subject.name,
+                minutes,
+                if minutes == 1 { "" } else { "s" }
+            );
+            let body = format!(
+                "{} at {}:00 in {}\n{}",
+                block.subject.r#type, block.time, block.classroom, block.professor
+            );
+            if let Err(err) = self.notifier.send(&summary, &body) {
+                if !self.failed {
+                    error = Some(err);
+                }
+                self.failed = true;
+            }
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the summaries it is asked to send, failing every time if `fail` is set.
+    #[derive(Debug, Default, Clone)]
+    struct Recorder {
+        sent: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl Notifier for Recorder {
+        fn send(&mut self, summary: &str, _body: &str) -> Result<(), String> {
+            if self.fail {
+                return Err("no notification daemon".into());
+            }
+            self.sent.lock().unwrap().push(summary.to_string());
+            Ok(())
+        }
+    }
+
+    fn config() -> ReminderConfig {
+        ReminderConfig {
+            enabled: true,
+            lead_minutes: 10,
+            muted: Vec::new(),
+        }
+    }
+
+    /// Monday, 20 October 2025 at the given time.
+    fn monday(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 10, 20, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn reminders() -> (Reminders, Arc<Mutex<Vec<String>>>) {
+        let recorder = Recorder::default();
+        let sent = recorder.sent.clone();
+        (Reminders::new(Box::new(recorder)), sent)
+    }
+
+    #[test]
+    fn reminds_within_the_lead_time() {
+        let (mut reminders, sent) = reminders();
+        let blocks = [
+            TimeBlock::sample(0, 10, 2, "Math", "P"),
+            TimeBlock::sample(0, 11, 1, "Physics", "P"),
+        ];
+        assert_eq!(
+            reminders.check(&config(), &blocks, &[], monday(9, 45)),
+            None
+        );
+        assert!(sent.lock().unwrap().is_empty());
+        reminders.check(&config(), &blocks, &[], monday(9, 50));
+        assert_eq!(*sent.lock().unwrap(), ["Math in 10 minutes"]);
+    }
+
+    #[test]
+    fn skips_other_days_and_started_classes() {
+        let (mut reminders, sent) = reminders();
+        let blocks = [
+            TimeBlock::sample(1, 10, 2, "Tuesday class", "P"),
+            TimeBlock::sample(0, 9, 2, "Started", "P"),
+        ];
+        reminders.check(&config(), &blocks, &[], monday(9, 55));
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_muted_subjects() {
+        let (mut reminders, sent) = reminders();
+        let config = ReminderConfig {
+            muted: vec!["Math".into()],
+            ..config()
+        };
+        let blocks = [
+            TimeBlock::sample(0, 10, 2, "Math", "P"),
+            TimeBlock::sample(0, 10, 2, "Physics", "LV"),
+        ];
+        reminders.check(&config, &blocks, &[], monday(9, 55));
+        assert_eq!(*sent.lock().unwrap(), ["Physics in 5 minutes"]);
+    }
+
+    #[test]
+    fn reminds_once_per_class() {
+        let (mut reminders, sent) = reminders();
+        let blocks = [TimeBlock::sample(0, 10, 2, "Math", "P")];
+        for minute in 50..60 {
+            reminders.check(&config(), &blocks, &[], monday(9, minute));
+        }
+        assert_eq!(*sent.lock().unwrap(), ["Math in 10 minutes"]);
+    }
+
+    #[test]
+    fn sends_nothing_on_holidays() {
+        let (mut reminders, sent) = reminders();
+        let holidays = [Holiday {
+            name: "Autumn break".into(),
+            from: monday(0, 0).date_naive(),
+            until: None,
+        }];
+        let blocks = [TimeBlock::sample(0, 10, 2, "Math", "P")];
+        reminders.check(&config(), &blocks, &holidays, monday(9, 55));
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_a_failing_backend_once() {
+        let mut reminders = Reminders::new(Box::new(Recorder {
+            fail: true,
+            ..Recorder::default()
+        }));
+        let blocks = [
+            TimeBlock::sample(0, 10, 2, "Math", "P"),
+            TimeBlock::sample(0, 10, 2, "Physics", "P"),
+        ];
+        let first = reminders.check(&config(), &blocks, &[], monday(9, 55));
+        assert_eq!(first.as_deref(), Some("no notification daemon"));
+        let blocks = [TimeBlock::sample(0, 11, 1, "Chemistry", "P")];
+        assert_eq!(
+            reminders.check(&config(), &blocks, &[], monday(10, 55)),
+            None
+        );
+    }
+
+    #[test]
+    fn is_due_once_a_minute() {
+        let (mut reminders, _) = reminders();
+        assert!(reminders.due(monday(9, 55)));
+        assert!(!reminders.due(monday(9, 55)));
+        assert!(reminders.due(monday(9, 56)));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::app::TimeBlock;
+use crate::validate::Problem;
+use serde::Deserializer;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+/// Newest payload version this build understands.
+pub const LATEST_VERSION: u64 = 2;
+
+/// Identity of a block that stays the same across refreshes.
+///
+/// Made of the source and either the id the API gave the block or, without one, when and where
+/// it takes place.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BlockId(pub String);
+
+impl BlockId {
+    pub fn new(source: &str, block: &TimeBlock) -> BlockId {
+        match &block.id {
+            Some(id) => BlockId(format!("{}#id:{}", source, id)),
+            None => BlockId::derived(source, block),
+        }
+    }
+
+    /// The id built from the day, time, subject, type and classroom of the block.
+    pub fn derived(source: &str, block: &TimeBlock) -> BlockId {
+        BlockId(format!(
+            "{}#{}-{}-{}-{}-{}",
+            source,
+            block.day,
+            block.time,
+            block.subject.name,
+            block.subject.r#type,
+            block.classroom
+        ))
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Blocks read from a timetable, with the problems of the ones that were skipped.
+#[derive(Debug, Default)]
+pub struct Decoded {
+    pub blocks: Vec<TimeBlock>,
+    pub skipped: Vec<Problem>,
+}
+
+/// Default duration of a block.
+pub fn one_hour() -> usize {
+    1
+}
+
+/// Reads a string without the whitespace around it.
+pub fn trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    <String as serde::Deserialize>::deserialize(deserializer).map(|value| value.trim().to_string())
+}
+
+/// Payload of version 2 and later.
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    version: Option<u64>,
+    blocks: Option<serde_json::Value>,
+}
+
+/// Decodes a timetable in any of the supported shapes.
+///
+/// Version 1 is a bare list of blocks. Version 2 wraps the list as
+/// `{"version": 2, "blocks": [...]}` and may give every block an `id`.
+/// Blocks that cannot be read are skipped, only a body that is not a list of blocks at all is an
+/// error.
+pub fn decode(body: &str) -> Result<Decoded, String> {
+    let value: serde_json::Value = serde_json::from_str(body.trim())
+        .map_err(|err| format!("The timetable is not valid JSON\n{}", err))?;
+    let blocks = match value {
+        serde_json::Value::Array(_) => value,
+        serde_json::Value::Object(_) => {
+            let envelope: Envelope = serde_json::from_value(value)
+                .map_err(|err| format!("Unknown timetable format\n{}", err))?;
+            match envelope.version {
+                Some(1..=LATEST_VERSION) => {}
+                Some(version) => {
+                    return Err(format!(
+                        "The timetable uses version {}, but this tab-term only reads versions \
+                        1 to {}\nUpdate tab-term to read it",
+                        version, LATEST_VERSION
+                    ));
+                }
+                None => return Err("The timetable has no `version`".into()),
+            }
+            envelope
+                .blocks
+                .ok_or("The timetable has no `blocks` list")?
+        }
+        _ => return Err("The timetable is neither a list of blocks nor an object".into()),
+    };
+    let serde_json::Value::Array(entries) = blocks else {
+        return Err("The timetable `blocks` are not a list".into());
+    };
+    let mut decoded = Decoded::default();
+    for (idx, entry) in entries.into_iter().enumerate() {
+        // Name the block by its subject if it has one, as the position alone is hard to find
+        let name = entry
+            .pointer("/subject/name")
+            .and_then(|name| name.as_str())
+            .map(|name| format!(" ({})", name))
+            .unwrap_or_default();
+        match serde_json::from_value(entry) {
+            Ok(block) => decoded.blocks.push(block),
+            Err(err) => decoded.skipped.push(Problem {
+                block: format!("Block {}{}", idx + 1, name),
+                reason: err.to_string(),
+            }),
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bare_list_is_version_one() {
+        let decoded = decode(
+            r#"[{"day": 1, "time": 9, "duration": 2, "professor": "Novak",
+                "classroom": "P1", "subject": {"name": "Math", "type": "P"}}]"#,
+        )
+        .unwrap();
+        assert!(decoded.skipped.is_empty());
+        let block = &decoded.blocks[0];
+        assert_eq!((block.day, block.time, block.duration), (1, 9, 2));
+        assert_eq!(block.subject.name, "Math");
+        assert_eq!(block.id, None);
+    }
+
+    #[test]
+    fn the_envelope_carries_ids() {
+        let decoded = decode(
+            r#"{"version": 2, "blocks": [
+                {"id": "m-1", "day": 0, "time": 8, "subject": {"name": "Math"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(decoded.blocks[0].id.as_deref(), Some("m-1"));
+        assert_eq!(
+            BlockId::new("src", &decoded.blocks[0]),
+            BlockId("src#id:m-1".into())
+        );
+    }
+
+    #[test]
+    fn newer_and_missing_versions_are_rejected() {
+        let newer = decode(r#"{"version": 3, "blocks": []}"#).unwrap_err();
+        assert!(newer.contains("version 3"), "{}", newer);
+        assert!(newer.contains("Update tab-term"), "{}", newer);
+        let missing = decode(r#"{"blocks": []}"#).unwrap_err();
+        assert!(missing.contains("no `version`"), "{}", missing);
+        let no_blocks = decode(r#"{"version": 2}"#).unwrap_err();
+        assert!(no_blocks.contains("no `blocks`"), "{}", no_blocks);
+    }
+
+    #[test]
+    fn bodies_that_are_not_timetables_are_errors() {
+        assert!(decode("not json").unwrap_err().contains("not valid JSON"));
+        assert!(decode("42").unwrap_err().contains("neither a list"));
+        assert!(
+            decode(r#"{"version": 2, "blocks": {}}"#)
+                .unwrap_err()
+                .contains("are not a list")
+        );
+    }
+
+    #[test]
+    fn unreadable_blocks_are_skipped_with_the_reason() {
+        let decoded = decode(
+            r#"[{"day": 0, "time": 8, "subject": {"name": "Math"}},
+                {"day": "monday", "time": 8, "subject": {"name": "Physics"}},
+                {"day": 0, "subject": {"name": "Art"}},
+                {"day": 0, "time": 8}]"#,
+        )
+        .unwrap();
+        assert_eq!(decoded.blocks.len(), 1);
+        let blocks: Vec<&str> = decoded
+            .skipped
+            .iter()
+            .map(|problem| problem.block.as_str())
+            .collect();
+        assert_eq!(blocks, ["Block 2 (Physics)", "Block 3 (Art)", "Block 4"]);
+        assert!(decoded.skipped[1].reason.contains("time"));
+    }
+
+    #[test]
+    fn optional_fields_have_defaults() {
+        let decoded = decode(
+            r#"[{"day": 2, "time": 10, "subject": {"name": "Math", "type": "  LV "},
+                "building": "ignored"}]"#,
+        )
+        .unwrap();
+        let block = &decoded.blocks[0];
+        assert_eq!(block.duration, 1);
+        assert_eq!(block.professor, "");
+        assert_eq!(block.classroom, "");
+        assert_eq!(block.subject.r#type, "LV");
+        assert!(block.except.is_empty() && block.weeks.is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use crate::app::TimeBlock;
+
+/// Text search across the blocks of the timetable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Search {
+    pub query: String,
+    /// Whether keys are still typed into the query
+    pub editing: bool,
+}
+
+impl Search {
+    /// Case-insensitive match against the subject name, abbreviation, professor, classroom and
+    /// type. An empty query matches nothing.
+    pub fn matches(&self, block: &TimeBlock) -> bool {
+        let query = self.query.trim().to_lowercase();
+        if query.is_empty() {
+            return false;
+        }
+        [
+            &block.subject.name,
+            &block.subject.abbreviation,
+            &block.professor,
+            &block.classroom,
+            &block.subject.r#type,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// Indices of the matching blocks.
+    pub fn match_indices(&self, blocks: &[TimeBlock]) -> Vec<usize> {
+        blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| self.matches(block))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
@@ -0,0 +1,183 @@
+use crate::app::TimeBlock;
+use crate::ui::DAYS;
+use std::collections::BTreeMap;
+
+/// Hours and times of the classes of a single day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayStats {
+    pub hours: usize,
+    /// Start of the first class
+    pub first: Option<usize>,
+    /// End of the last class
+    pub last: Option<usize>,
+    /// Free hours between classes as (from, until)
+    pub gaps: Vec<(usize, usize)>,
+}
+
+impl DayStats {
+    pub fn gap_hours(&self) -> usize {
+        self.gaps.iter().map(|(from, until)| until - from).sum()
+    }
+}
+
+/// Contact hours of a week of classes.
+///
+/// Classes at the same time count once, so parallel sessions of a subject and type add no hours.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub total_hours: usize,
+    /// Hours of every subject, split by type
+    pub subjects: BTreeMap<String, BTreeMap<String, usize>>,
+    pub days: Vec<DayStats>,
+    /// Whether parallel sessions at different times were all counted, as no group was picked
+    pub unpicked_groups: bool,
+}
+
+/// Merges overlapping and adjoining hours as (from, until) into the hours taken up by any of them.
+fn merged(mut hours: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    hours.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (from, until) in hours {
+        match merged.last_mut() {
+            Some((_, end)) if from <= *end => *end = (*end).max(until),
+            _ => merged.push((from, until)),
+        }
+    }
+    merged
+}
+
+fn length(hours: &[(usize, usize)]) -> usize {
+    hours.iter().map(|(from, until)| until - from).sum()
+}
+
+impl Stats {
+    pub fn compute(blocks: &[TimeBlock]) -> Stats {
+        let mut stats = Stats {
+            days: vec![DayStats::default(); DAYS.len()],
+            ..Stats::default()
+        };
+        let hours_of = |blocks: &[&TimeBlock], day: usize| {
+            merged(
+                blocks
+                    .iter()
+                    .filter(|block| block.day == day)
+                    .map(|block| (block.time, block.time + block.duration))
+                    .collect(),
+            )
+        };
+        let mut by_subject: BTreeMap<(&str, &str), Vec<&TimeBlock>> = BTreeMap::new();
+        for block in blocks {
+            by_subject
+                .entry((&block.subject.name, &block.subject.r#type))
+                .or_default()
+                .push(block);
+        }
+        for ((name, r#type), blocks) in by_subject {
+            let hours = (0..DAYS.len())
+                .map(|day| length(&hours_of(&blocks, day)))
+                .sum();
+            stats
+                .subjects
+                .entry(name.to_string())
+                .or_default()
+                .insert(r#type.to_string(), hours);
+        }
+        let blocks: Vec<&TimeBlock> = blocks.iter().collect();
+        for (day, day_stats) in stats.days.iter_mut().enumerate() {
+            let hours = hours_of(&blocks, day);
+            day_stats.hours = length(&hours);
+            day_stats.first = hours.first().map(|(from, _)| *from);
+            day_stats.last = hours.last().map(|(_, until)| *until);
+            day_stats.gaps = hours
+                .windows(2)
+                .map(|pair| (pair[0].1, pair[1].0))
+                .collect();
+            stats.total_hours += day_stats.hours;
+        }
+        stats
+    }
+
+    /// The day with the most hours of classes, the earlier one on a tie.
+    pub fn busiest_day(&self) -> Option<usize> {
+        self.days
+            .iter()
+            .enumerate()
+            .filter(|(_, day)| day.hours > 0)
+            .max_by_key(|(day, stats)| (stats.hours, std::cmp::Reverse(*day)))
+            .map(|(day, _)| day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_first_last_and_gaps_of_a_day() {
+        let stats = Stats::compute(&[
+            TimeBlock::sample(0, 8, 2, "Math", "P"),
+            TimeBlock::sample(0, 10, 1, "Physics", "P"),
+            TimeBlock::sample(0, 13, 2, "Math", "LV"),
+            TimeBlock::sample(0, 17, 1, "Art", "P"),
+        ]);
+        assert_eq!(
+            stats.days[0],
+            DayStats {
+                hours: 6,
+                first: Some(8),
+                last: Some(18),
+                gaps: vec![(11, 13), (15, 17)],
+            }
+        );
+        assert_eq!(stats.days[0].gap_hours(), 4);
+        assert_eq!(stats.days[1], DayStats::default());
+        assert_eq!(stats.total_hours, 6);
+    }
+
+    #[test]
+    fn parallel_sessions_count_once() {
+        let stats = Stats::compute(&[
+            TimeBlock::sample(1, 8, 2, "Math", "LV"),
+            TimeBlock::sample(1, 8, 2, "Math", "LV"),
+            // Overlapping another subject
+            TimeBlock::sample(1, 9, 2, "Physics", "P"),
+        ]);
+        assert_eq!(stats.days[1].hours, 3);
+        assert_eq!(
+            (stats.days[1].first, stats.days[1].last),
+            (Some(8), Some(11))
+        );
+        assert!(stats.days[1].gaps.is_empty());
+        assert_eq!(stats.total_hours, 3);
+        assert_eq!(stats.subjects["Math"]["LV"], 2);
+        assert_eq!(stats.subjects["Physics"]["P"], 2);
+    }
+
+    #[test]
+    fn subjects_are_split_by_type() {
+        let stats = Stats::compute(&[
+            TimeBlock::sample(0, 8, 2, "Math", "P"),
+            TimeBlock::sample(2, 8, 2, "Math", "P"),
+            TimeBlock::sample(3, 12, 3, "Math", "LV"),
+        ]);
+        let math: Vec<(&str, usize)> = stats.subjects["Math"]
+            .iter()
+            .map(|(r#type, hours)| (r#type.as_str(), *hours))
+            .collect();
+        assert_eq!(math, [("LV", 3), ("P", 4)]);
+        assert_eq!(stats.total_hours, 7);
+    }
+
+    #[test]
+    fn the_busiest_day_is_the_earlier_one_on_a_tie() {
+        let stats = Stats::compute(&[
+            TimeBlock::sample(1, 8, 3, "Math", "P"),
+            TimeBlock::sample(3, 8, 3, "Physics", "P"),
+            TimeBlock::sample(4, 8, 2, "Art", "P"),
+            // Parallel sessions do not make a day busier
+            TimeBlock::sample(4, 8, 2, "Art", "P"),
+        ]);
+        assert_eq!(stats.busiest_day(), Some(1));
+        assert_eq!(Stats::compute(&[]).busiest_day(), None);
+    }
+}
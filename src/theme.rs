@@ -0,0 +1,204 @@
+use crate::palette::ColorMode;
+use ratatui::style::Color;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Colors used to draw the interface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Theme {
+    /// Day columns and popup frames
+    pub border: Color,
+    /// Day names and popup section headers
+    pub header: Color,
+    pub text: Color,
+    /// Hints, labels and blocks that are dimmed
+    pub dim: Color,
+    /// Subjects without an assigned color
+    pub fallback: Color,
+    pub info: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Line marking the current time
+    pub now_line: Color,
+    /// Border of the selected block
+    pub selection: Color,
+    /// The user's own events
+    pub personal: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            border: Color::DarkGray,
+            header: Color::White,
+            text: Color::White,
+            dim: Color::DarkGray,
+            fallback: Color::Red,
+            info: Color::Cyan,
+            warning: Color::Yellow,
+            error: Color::Red,
+            now_line: Color::LightRed,
+            selection: Color::White,
+            personal: Color::LightMagenta,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            border: Color::Gray,
+            header: Color::Black,
+            text: Color::Black,
+            dim: Color::Gray,
+            fallback: Color::Red,
+            info: Color::Blue,
+            warning: Color::Rgb(179, 89, 0),
+            error: Color::Red,
+            now_line: Color::Red,
+            selection: Color::Black,
+            personal: Color::Magenta,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Theme {
+            border: Color::White,
+            header: Color::White,
+            text: Color::White,
+            dim: Color::Gray,
+            fallback: Color::LightRed,
+            info: Color::LightCyan,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            now_line: Color::LightMagenta,
+            selection: Color::LightYellow,
+            personal: Color::LightMagenta,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Looks up the theme by name among the user themes and the built-in ones, then applies the
+    /// overrides on top of it.
+    ///
+    /// Color keys that are not part of a theme are left out and returned as problems.
+    pub fn resolve(config: &ThemeConfig) -> Result<(Theme, Vec<String>), String> {
+        let mut problems = Vec::new();
+        let theme = match config.themes.get(&config.name) {
+            Some(user) => {
+                let base = user.base.as_deref().unwrap_or("dark");
+                let base = Theme::builtin(base).ok_or_else(|| {
+                    format!(
+                        "Theme `{}` is based on unknown theme `{}`",
+                        config.name, base
+                    )
+                })?;
+                let (theme, unknown) = base
+                    .with_overrides(&user.colors)
+                    .map_err(|err| format!("Invalid colors in theme `{}`\n{}", config.name, err))?;
+                problems.extend(
+                    unknown
+                        .iter()
+                        .map(|key| format!("Unknown color `{}` in theme `{}`", key, config.name)),
+                );
+                theme
+            }
+            None => Theme::builtin(&config.name).ok_or_else(|| {
+                format!(
+                    "Unknown theme `{}`\nBuilt-in themes are dark, light and high-contrast",
+                    config.name
+                )
+            })?,
+        };
+        let (theme, unknown) = theme
+            .with_overrides(&config.overrides)
+            .map_err(|err| format!("Invalid theme overrides\n{}", err))?;
+        problems.extend(
+            unknown
+                .iter()
+                .map(|key| format!("Unknown color `{}` in the theme overrides", key)),
+        );
+        Ok((theme, problems))
+    }
+
+    /// The theme with every color mapped to what the terminal can show.
+    pub fn mapped(&self, mode: ColorMode) -> Theme {
+        Theme {
+            border: mode.map(self.border),
+            header: mode.map(self.header),
+            text: mode.map(self.text),
+            dim: mode.map(self.dim),
+            fallback: mode.map(self.fallback),
+            info: mode.map(self.info),
+            warning: mode.map(self.warning),
+            error: mode.map(self.error),
+            now_line: mode.map(self.now_line),
+            selection: mode.map(self.selection),
+            personal: mode.map(self.personal),
+        }
+    }
+
+    /// Replaces the colors named in `overrides`, e.g. `{"border": "blue"}`, returning the keys
+    /// that are not colors of a theme.
+    fn with_overrides(
+        &self,
+        overrides: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(Theme, Vec<String>), serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        let mut unknown = Vec::new();
+        if let Some(fields) = value.as_object_mut() {
+            for (key, color) in overrides {
+                match fields.get_mut(key) {
+                    Some(field) => *field = color.clone(),
+                    None => unknown.push(key.clone()),
+                }
+            }
+        }
+        Ok((serde_json::from_value(value)?, unknown))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// A theme defined in the settings, made of a built-in base and the colors it changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserTheme {
+    /// Built-in theme to start from, `dark` if not set
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The `theme` section of the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Name of a built-in or user theme
+    pub name: String,
+    /// User themes by name
+    pub themes: BTreeMap<String, UserTheme>,
+    /// Colors replaced on top of the selected theme
+    pub overrides: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig {
+            name: "dark".into(),
+            themes: BTreeMap::new(),
+            overrides: serde_json::Map::new(),
+        }
+    }
+}
@@ -1,60 +1,235 @@
-use crate::app::{Subject, TimeBlock};
+use crate::app::{Origin, Popup, Subject, TimeBlock};
+use crate::calendar::{self, Holiday};
+use crate::diff::Change;
+use crate::free::FreeTime;
+use crate::groups::GroupStatus;
+use crate::keymap::{Action, Context};
+use crate::notes::NoteForm;
+use crate::notification::{Notification, Severity};
+use crate::palette::ColorMode;
+use crate::personal::EventForm;
+use crate::search::Search;
+use chrono::{Datelike, Timelike};
 use ratatui::layout::Rect;
 use ratatui::{
     buffer::Buffer,
     prelude::*,
     style::{Color, Stylize},
-    widgets::{Block, BorderType, Borders, Paragraph, Widget},
+    widgets::{
+        Bar, BarChart, BarGroup, Block, BorderType, Borders, Clear, List, ListItem, ListState,
+        Paragraph, Row, Table, Widget, Wrap,
+    },
 };
+use std::rc::Rc;
 
 use crate::app::App;
 
-const ROW_DISPLAY_COUNT: usize = 21 - 7;
+/// The first hour shown in the grid
+pub const FIRST_HOUR: usize = 7;
+/// The hour the grid ends at
+pub const LAST_HOUR: usize = 21;
+const ROW_DISPLAY_COUNT: usize = LAST_HOUR - FIRST_HOUR;
 const ROW_CONSTRAINT_PERCENTAGE: u16 = (100.0 / ROW_DISPLAY_COUNT as f32) as u16 - 1;
-fn map_idx_to_time(index: usize) -> usize {
-    index + 7
+/// Row of the hour, `None` before the grid starts.
+fn map_time_to_idx(time: usize) -> Option<usize> {
+    time.checked_sub(FIRST_HOUR)
 }
-const DAYS: [&str; 5] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"];
+pub const DAYS: [&str; 5] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"];
 
-fn get_color(sub: &Subject, c_list: &Vec<(String, Color)>) -> Color {
+fn get_color(sub: &Subject, c_list: &[(String, Color)], fallback: Color) -> Color {
     for i in c_list {
         if i.0 == sub.name {
             return i.1;
         }
     }
-    Color::Red
+    fallback
 }
+/// A block together with its position inside the day column.
+#[derive(Debug, Clone)]
+pub struct PlacedBlock {
+    pub block: TimeBlock,
+    /// Lane the block is drawn in, counted from the left
+    pub lane: usize,
+    /// Number of lanes the overlapping group is split into
+    pub lanes: usize,
+}
+
+/// Border used to tell subjects apart when colors are disabled.
+///
+/// Thick, double and rounded borders are left out as they mark the selection, search matches and
+/// the user's own events.
+fn subject_border(sub: &Subject, c_list: &[(String, Color)]) -> BorderType {
+    const BORDERS: [BorderType; 3] = [
+        BorderType::Plain,
+        BorderType::QuadrantOutside,
+        BorderType::QuadrantInside,
+    ];
+    let idx = c_list.iter().position(|i| i.0 == sub.name).unwrap_or(0);
+    BORDERS[idx % BORDERS.len()]
+}
+
 // First vec is for each day
-// Second vec is for each hour
-// Third is for all the subjects on that same hour and day
-fn group_by_time(blocks: &Vec<TimeBlock>) -> Vec<Vec<Vec<TimeBlock>>> {
-    let mut out: Vec<Vec<_>> = Vec::new();
-    let mut day_filtered: Vec<_> = Vec::new();
-    for d in 0..5 {
-        let blocks_day_filtered = blocks
-            .clone()
-            .into_iter()
-            .filter(|block| block.day == d)
-            .collect::<Vec<TimeBlock>>();
-        day_filtered.push(blocks_day_filtered);
-    }
-    for d in day_filtered {
-        let mut tmp = Vec::new();
-        for h in 0..ROW_DISPLAY_COUNT {
-            let blocks_time_filtered = d
-                .clone()
-                .into_iter()
-                .filter(|block| block.time == map_idx_to_time(h))
-                .collect::<Vec<TimeBlock>>();
-            if !blocks_time_filtered.is_empty() {
-                tmp.push(blocks_time_filtered);
+// Second vec is every block of that day with its lane assigned
+fn group_by_time(blocks: &[TimeBlock]) -> Vec<Vec<PlacedBlock>> {
+    (0..DAYS.len())
+        .map(|d| {
+            assign_lanes(
+                blocks
+                    .iter()
+                    .filter(|block| block.day == d)
+                    .cloned()
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// Lays out the blocks of a single day the way calendar apps do.
+///
+/// Blocks are visited by start time and each one takes the leftmost lane that is free when it
+/// starts. Blocks that overlap, directly or through a chain of other blocks, form a cluster and
+/// all of them are drawn with the lane count of the widest point of that cluster.
+fn assign_lanes(mut blocks: Vec<TimeBlock>) -> Vec<PlacedBlock> {
+    // Longer blocks first on equal start, so they end up in the leftmost lanes
+    blocks.sort_by_key(|block| (block.time, std::cmp::Reverse(block.duration)));
+
+    let mut out = Vec::new();
+    let mut cluster: Vec<PlacedBlock> = Vec::new();
+    // End hour of the last block placed in each lane of the current cluster
+    let mut lane_ends: Vec<usize> = Vec::new();
+    let mut cluster_end = 0;
+    for block in blocks {
+        if block.time >= cluster_end {
+            close_cluster(&mut cluster, lane_ends.len(), &mut out);
+            lane_ends.clear();
+        }
+        let end = block.time + block.duration.max(1);
+        let lane = match lane_ends
+            .iter()
+            .position(|&lane_end| lane_end <= block.time)
+        {
+            Some(free) => {
+                lane_ends[free] = end;
+                free
             }
+            None => {
+                lane_ends.push(end);
+                lane_ends.len() - 1
+            }
+        };
+        cluster_end = cluster_end.max(end);
+        cluster.push(PlacedBlock {
+            block,
+            lane,
+            lanes: 0,
+        });
+    }
+    close_cluster(&mut cluster, lane_ends.len(), &mut out);
+    out
+}
+
+fn close_cluster(cluster: &mut Vec<PlacedBlock>, lanes: usize, out: &mut Vec<PlacedBlock>) {
+    for mut placed in cluster.drain(..) {
+        placed.lanes = lanes;
+        out.push(placed);
+    }
+}
+
+/// Areas of the grid, shared by drawing and mouse hit-testing.
+pub struct GridLayout {
+    /// Day names above each column
+    pub days: Rc<[Rect]>,
+    pub columns: Rc<[Rect]>,
+    /// The hour rows of each column
+    pub rows: Vec<Rc<[Rect]>>,
+}
+
+pub fn grid_layout(area: Rect) -> GridLayout {
+    let base_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(5), Constraint::Percentage(95)])
+        .split(area);
+    let columns_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints({
+            let mut constraints = Vec::new();
+            for _ in 0..5 {
+                constraints.push(Constraint::Percentage(20));
+            }
+            constraints
+        })
+        .split(base_layout[1]);
+    let days_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints({
+            let mut constraints = Vec::new();
+            for _ in 0..5 {
+                constraints.push(Constraint::Percentage(20));
+            }
+            constraints
+        })
+        .split(base_layout[0]);
+
+    let mut rows_layout: Vec<_> = Vec::new();
+    for column in 0..columns_layout.len() {
+        rows_layout.push(
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints({
+                    let mut constraints = Vec::new();
+                    for _ in 0..ROW_DISPLAY_COUNT {
+                        constraints.push(Constraint::Percentage(ROW_CONSTRAINT_PERCENTAGE));
+                    }
+                    constraints
+                })
+                .split(columns_layout[column]),
+        );
+    }
+    GridLayout {
+        days: days_layout,
+        columns: columns_layout,
+        rows: rows_layout,
+    }
+}
+
+/// Where each of the blocks is drawn in the grid.
+pub fn block_areas(blocks: &[TimeBlock], rows_layout: &[Rc<[Rect]>]) -> Vec<(Rect, PlacedBlock)> {
+    let mut out = Vec::new();
+    for (d, day) in group_by_time(blocks).into_iter().enumerate() {
+        for placed in day {
+            let block = &placed.block;
+            // If the class is longer than 1h, merge the rows
+            let row = |hour| map_time_to_idx(hour).and_then(|idx| rows_layout.get(d)?.get(idx));
+            // Blocks outside of the grid are reported when loading, not drawn
+            let (Some(first), Some(last)) =
+                (row(block.time), row(block.time + block.duration.max(1) - 1))
+            else {
+                continue;
+            };
+            let area_render = first.union(*last);
+            // Split the cell into one column per lane of its overlap group
+            let split_area = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Ratio(1, placed.lanes as u32);
+                    placed.lanes
+                ])
+                .split(area_render);
+            out.push((split_area[placed.lane], placed));
         }
-        out.push(tmp);
     }
     out
 }
 
+/// The block drawn at the given terminal cell, if any.
+pub fn block_at(app: &App, area: Rect, column: u16, row: u16) -> Option<TimeBlock> {
+    let grid = grid_layout(area);
+    block_areas(&app.visible_blocks(), &grid.rows)
+        .into_iter()
+        .find(|(rect, _)| rect.contains(Position::new(column, row)))
+        .map(|(_, placed)| placed.block)
+}
+
 impl Widget for &App {
     /// Renders the user interface widgets.
     ///
@@ -63,97 +238,1000 @@ impl Widget for &App {
     // - https://docs.rs/ratatui/latest/ratatui/widgets/index.html
     // - https://github.com/ratatui/ratatui/tree/master/examples
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let base_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(5), Constraint::Percentage(95)])
-            .split(area);
-        let columns_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints({
-                let mut constraints = Vec::new();
-                for _ in 0..5 {
-                    constraints.push(Constraint::Percentage(20));
-                }
-                constraints
-            })
-            .split(base_layout[1]);
-        let days_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints({
-                let mut constraints = Vec::new();
-                for _ in 0..5 {
-                    constraints.push(Constraint::Percentage(20));
-                }
-                constraints
-            })
-            .split(base_layout[0]);
-
-        let mut rows_layout: Vec<_> = Vec::new();
-        for column in 0..columns_layout.len() {
-            rows_layout.push(
-                Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints({
-                        let mut constraints = Vec::new();
-                        for _ in 0..ROW_DISPLAY_COUNT {
-                            constraints.push(Constraint::Percentage(ROW_CONSTRAINT_PERCENTAGE));
-                        }
-                        constraints
-                    })
-                    .split(columns_layout[column]),
-            );
-        }
-        for col in columns_layout.iter() {
+        let GridLayout {
+            days: days_layout,
+            columns: columns_layout,
+            rows: rows_layout,
+        } = grid_layout(area);
+        // The grid border flashes every second while a class is about to start
+        let border_color =
+            if self.alert.is_some() && chrono::Local::now().second().is_multiple_of(2) {
+                self.theme.warning
+            } else {
+                self.theme.border
+            };
+        for (c, col) in columns_layout.iter().enumerate() {
+            let mut column = Block::default().borders(Borders::ALL);
+            // Show which timetable is active when there is more than one
+            if let (0, Some(source), 2..) = (c, self.source(), self.sources.len()) {
+                column = column.title_bottom(format!(
+                    "{}/{} {}",
+                    self.source_index + 1,
+                    self.sources.len(),
+                    source
+                ));
+            }
+            if c == columns_layout.len() - 1 {
+                column = column.title_bottom(Line::from(week_label(self)).right_aligned());
+            }
             Paragraph::default()
-                .block(Block::default().borders(Borders::ALL))
-                .fg(Color::DarkGray)
+                .block(column)
+                .fg(border_color)
                 .render(*col, buf);
         }
 
+        let monday = self.week_start();
         for day in 0..5 {
-            Paragraph::new(DAYS[day])
+            let date = calendar::date_of(monday, day);
+            Paragraph::new(format!("{} {}", DAYS[day], date.format("%-d.%-m.")))
                 .block(Block::default().borders(Borders::RIGHT))
                 .alignment(Alignment::Center)
-                .fg(Color::White)
+                .fg(self.theme.header)
                 .render(days_layout[day], buf);
         }
 
-        let blocks_grouped = group_by_time(&self.timetable_data);
-        for (d, day) in blocks_grouped.iter().enumerate() {
-            for hour in day {
-                for (b, block) in hour.iter().enumerate() {
-                    // If the class is longer than 1h, merge the rows
-                    let idx = block.time - 7;
-                    let mut area_render = rows_layout[d][idx];
-                    if block.duration > 1 {
-                        area_render = area_render.union(rows_layout[d][idx + (block.duration - 1)]);
-                    }
-                    // By how many columns will the cell be split up
-                    let split_by = hour.len();
-                    // Get the right constraints by dividing 100% with the above
-                    let split_area = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints({
-                            let mut constraints = Vec::new();
-                            for _ in 0..split_by {
-                                constraints
-                                    .push(Constraint::Percentage((100.0 / split_by as f32) as u16));
-                            }
-                            constraints
-                        })
-                        .split(area_render);
-                    // Render the block in its own column
-                    let block_render = Block::default()
-                        .border_type(BorderType::Plain)
-                        .fg(get_color(&block.subject, &self.colors))
-                        .title(format!("{}:00", &block.time))
-                        .borders(Borders::ALL);
-                    Paragraph::new(block.format_block())
-                        .block(block_render)
-                        .fg(Color::White)
-                        .render(split_area[b], buf);
-                }
+        if self.free_time != FreeTime::Off {
+            render_free_time(self, &rows_layout, buf);
+        }
+        let selected = self.selected_block();
+        for (area_render, placed) in block_areas(&self.visible_blocks(), &rows_layout) {
+            let block = &placed.block;
+            // While searching, only the matches keep their colors
+            let search_match = self
+                .search
+                .as_ref()
+                .filter(|search| !search.query.trim().is_empty())
+                .map(|search| search.matches(block));
+            // Parallel sessions the user does not attend are dimmed
+            let (border_color, text_color) = match (self.group_status(block), search_match) {
+                (_, Some(false)) | (GroupStatus::Other, None) => (self.theme.dim, self.theme.dim),
+                _ if block.origin == Origin::Personal => (self.theme.personal, self.theme.text),
+                _ => (
+                    get_color(&block.subject, &self.colors, self.theme.fallback),
+                    self.theme.text,
+                ),
+            };
+            let is_selected = selected.as_ref() == Some(block);
+            let border_color = if is_selected {
+                self.theme.selection
+            } else {
+                border_color
+            };
+            let border_type = if is_selected {
+                BorderType::Thick
+            } else if search_match == Some(true) {
+                BorderType::Double
+            } else if block.origin == Origin::Personal {
+                BorderType::Rounded
+            } else if self.color_mode == ColorMode::NoColor {
+                subject_border(&block.subject, &self.colors)
+            } else {
+                BorderType::Plain
+            };
+            // Without colors the abbreviation tells subjects apart as well
+            let mut title = if block.origin == Origin::Personal {
+                Line::from(format!("◆ {}:00", block.time))
+            } else if self.color_mode == ColorMode::NoColor {
+                Line::from(format!("{}:00 {}", block.time, block.subject.abbreviation))
+            } else {
+                Line::from(format!("{}:00", block.time))
+            };
+            // Blocks that changed in the last refresh
+            if self
+                .changes
+                .iter()
+                .any(|change| change.current() == Some(block))
+            {
+                title.push_span(Span::raw(" ●").fg(self.theme.warning));
+            }
+            // Blocks with a note of their own or on their subject
+            if let (Some(_), _) | (_, Some(_)) = self.notes_for(block) {
+                title.push_span(Span::raw(" ✎").fg(self.theme.info));
+            }
+            // Render the block in its own lane
+            let block_render = Block::default()
+                .border_type(border_type)
+                .fg(border_color)
+                .title(title)
+                .borders(Borders::ALL);
+            Paragraph::new(block.format_block())
+                .block(block_render)
+                .fg(text_color)
+                .render(area_render, buf);
+        }
+
+        for (day, column) in columns_layout.iter().enumerate() {
+            let date = calendar::date_of(monday, day);
+            if let Some(holiday) = calendar::holiday_on(&self.holidays, date) {
+                render_holiday(self, holiday, *column, buf);
+            }
+        }
+        if self.week_offset == 0 {
+            render_now_line(self, &columns_layout, &rows_layout, buf);
+        }
+        if let Some((block, minutes)) = &self.alert {
+            render_alert(self, block, *minutes, area, buf);
+        }
+        if let Some(search) = &self.search {
+            render_search(self, search, area, buf);
+        }
+        let popup = self
+            .popup
+            .as_ref()
+            .map(|popup| (popup, popup_rect(popup, area)));
+        match popup {
+            Some((Popup::Filters { cursor }, area)) => render_filters(self, *cursor, area, buf),
+            Some((Popup::Details, area)) => render_details(self, area, buf),
+            Some((Popup::Help { scroll }, area)) => render_help(self, *scroll, area, buf),
+            Some((Popup::Messages { scroll }, area)) => render_messages(self, *scroll, area, buf),
+            Some((Popup::Changes { scroll }, area)) => render_changes(self, *scroll, area, buf),
+            Some((Popup::Problems { scroll }, area)) => render_problems(self, *scroll, area, buf),
+            Some((Popup::Statistics, area)) => render_statistics(self, area, buf),
+            Some((Popup::Events { cursor }, area)) => render_events(self, *cursor, area, buf),
+            Some((Popup::EventForm(form), area)) => render_event_form(self, form, area, buf),
+            Some((Popup::NoteEditor(form), area)) => render_note_editor(self, form, area, buf),
+            None => {}
+        }
+        render_toasts(self, area, buf);
+        if let Some(notification) = self.notifications.current() {
+            render_notification(self, notification, area, buf);
+        }
+    }
+}
+
+/// Marks the free hours of every day, drawn before the blocks.
+fn render_free_time(app: &App, rows: &[Rc<[Rect]>], buf: &mut Buffer) {
+    let label = match app.free_time {
+        FreeTime::Everyone(_) => "Everyone is free",
+        _ => "Free",
+    };
+    for (day, free) in app.free_hours().into_iter().enumerate() {
+        let row = |hour| map_time_to_idx(hour).and_then(|idx| rows.get(day)?.get(idx));
+        for (from, until) in free {
+            let (Some(first), Some(last)) = (row(from), row(until - 1)) else {
+                continue;
+            };
+            Paragraph::new(vec![
+                Line::from(label).bold(),
+                Line::from(format!("{}:00 - {}:00", from, until)),
+            ])
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT)
+                    .border_type(BorderType::Thick),
+            )
+            .fg(app.theme.info)
+            .render(first.union(*last), buf);
+        }
+    }
+}
+
+/// Covers a day column that has no classes.
+fn render_holiday(app: &App, holiday: &Holiday, column: Rect, buf: &mut Buffer) {
+    let height = 4.min(column.height);
+    // Inside the column borders
+    let area = Rect {
+        x: column.x + 1,
+        y: column.y + (column.height - height) / 2,
+        width: column.width.saturating_sub(2),
+        height,
+    };
+    Clear.render(area, buf);
+    Paragraph::new(vec![
+        Line::from("No classes").bold(),
+        Line::from(holiday.name.as_str()),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .fg(app.theme.header)
+    .block(
+        Block::default()
+            .borders(Borders::TOP | Borders::BOTTOM)
+            .fg(app.theme.dim),
+    )
+    .render(area, buf);
+}
+
+/// ISO week number of the week shown, with its distance from the current week.
+fn week_label(app: &App) -> String {
+    let relative = match app.week_offset {
+        0 => String::new(),
+        1 => " (next week)".into(),
+        -1 => " (last week)".into(),
+        n if n > 0 => format!(" (in {} weeks)", n),
+        n => format!(" ({} weeks ago)", -n),
+    };
+    format!("Week {}{}", app.week_start().iso_week().week(), relative)
+}
+
+/// Marks the current time across today's column, only over cells no block is drawn on.
+fn render_now_line(app: &App, columns: &[Rect], rows: &[Rc<[Rect]>], buf: &mut Buffer) {
+    let now = chrono::Local::now();
+    let day = now.weekday().num_days_from_monday() as usize;
+    let hour = now.hour() as usize;
+    let (Some(column), Some(day_rows)) = (columns.get(day), rows.get(day)) else {
+        return;
+    };
+    let Some(row) = map_time_to_idx(hour).and_then(|idx| day_rows.get(idx)) else {
+        return;
+    };
+    let y = row.y + (row.height as u32 * now.minute() / 60) as u16;
+    for x in column.left() + 1..column.right().saturating_sub(1) {
+        let cell = &mut buf[(x, y)];
+        if cell.symbol() == " " {
+            cell.set_symbol("─").set_fg(app.theme.now_line);
+        }
+    }
+    buf[(column.left(), y)]
+        .set_symbol("▶")
+        .set_fg(app.theme.now_line);
+}
+
+/// Banner over the day names announcing the next class.
+fn render_alert(app: &App, block: &TimeBlock, minutes: i64, area: Rect, buf: &mut Buffer) {
+    let text = format!(
+        "{} ({}) starts in {} minute{} in {}",
+        block.subject.name,
+        block.subject.r#type,
+        minutes,
+        if minutes == 1 { "" } else { "s" },
+        block.classroom
+    );
+    let width = (text.chars().count() as u16 + 4).min(area.width);
+    let banner = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y,
+        width,
+        height: 3.min(area.height),
+    };
+    Clear.render(banner, buf);
+    Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .bold()
+        .fg(app.theme.warning)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick)
+                .fg(app.theme.warning),
+        )
+        .render(banner, buf);
+}
+
+/// Draws the query on the bottom line of the screen.
+fn render_search(app: &App, search: &Search, area: Rect, buf: &mut Buffer) {
+    let line_area = Rect {
+        y: area.bottom().saturating_sub(1),
+        height: 1.min(area.height),
+        ..area
+    };
+    let blocks = app.visible_blocks();
+    let matches = search.match_indices(&blocks);
+    let position = app
+        .selected
+        .and_then(|selected| matches.iter().position(|idx| *idx == selected))
+        .map(|pos| (pos + 1).to_string())
+        .unwrap_or_else(|| "-".into());
+    let keys = &app.keymap;
+    let hint = if search.editing {
+        format!(
+            "<{}> confirm, <{}> cancel",
+            keys.key_hint(Context::Search, Action::Confirm),
+            keys.key_hint(Context::Search, Action::Cancel)
+        )
+    } else {
+        format!(
+            "{}/{} matches, {}/{} next/previous, <{}> clear",
+            position,
+            matches.len(),
+            keys.key_hint(Context::Grid, Action::NextMatch),
+            keys.key_hint(Context::Grid, Action::PreviousMatch),
+            keys.key_hint(Context::Grid, Action::ClearSearch)
+        )
+    };
+    Clear.render(line_area, buf);
+    Line::from(vec![
+        Span::raw(format!("/{}", search.query)).fg(app.theme.text),
+        Span::raw(if search.editing { "_  " } else { "  " }).fg(app.theme.text),
+        Span::raw(hint).fg(app.theme.dim),
+    ])
+    .render(line_area, buf);
+}
+
+/// Where the popup is drawn, shared by drawing and mouse hit-testing.
+pub fn popup_rect(popup: &Popup, area: Rect) -> Rect {
+    let (percent_x, percent_y) = match popup {
+        Popup::Filters { .. } => (60, 70),
+        Popup::Details => (50, 50),
+        Popup::Help { .. } => (60, 80),
+        Popup::Messages { .. } | Popup::Changes { .. } | Popup::Problems { .. } => (70, 70),
+        Popup::Statistics => (80, 80),
+        Popup::Events { .. } => (60, 60),
+        Popup::EventForm(_) | Popup::NoteEditor(_) => (50, 40),
+    };
+    popup_area(area, percent_x, percent_y)
+}
+
+/// Centered area taking up the given percentage of `area`.
+fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(layout::Flex::Center);
+    let horizontal =
+        Layout::horizontal([Constraint::Percentage(percent_x)]).flex(layout::Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+fn severity_color(app: &App, severity: Severity) -> Color {
+    match severity {
+        Severity::Info => app.theme.info,
+        Severity::Warn => app.theme.warning,
+        Severity::Error => app.theme.error,
+    }
+}
+
+fn render_notification(app: &App, notification: &Notification, area: Rect, buf: &mut Buffer) {
+    let area = popup_area(area, 50, 40);
+    let waiting = app.notifications.pending.len() - 1;
+    let mut hint = format!(
+        "Press <{}> to close the window",
+        app.keymap.key_hint(Context::Popup, Action::Close)
+    );
+    if waiting > 0 {
+        hint.push_str(&format!(" ({} more)", waiting));
+    }
+    Clear.render(area, buf);
+    Paragraph::new(notification.message.as_str())
+        .wrap(Wrap { trim: false })
+        .fg(app.theme.text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(notification.title.as_str())
+                .title_bottom(hint)
+                .fg(severity_color(app, notification.severity)),
+        )
+        .render(area, buf);
+}
+
+/// Stacks the info toasts in the top right corner, newest at the bottom.
+fn render_toasts(app: &App, area: Rect, buf: &mut Buffer) {
+    let width = (area.width / 3).max(20).min(area.width);
+    let mut y = area.y + 1;
+    for (toast, _) in &app.notifications.toasts {
+        let height = toast.message.lines().count() as u16 + 2;
+        if y + height > area.bottom() {
+            break;
+        }
+        let toast_area = Rect {
+            x: area.right() - width,
+            y,
+            width,
+            height,
+        };
+        Clear.render(toast_area, buf);
+        Paragraph::new(toast.message.as_str())
+            .fg(app.theme.text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(toast.title.as_str())
+                    .fg(severity_color(app, toast.severity)),
+            )
+            .render(toast_area, buf);
+        y += height;
+    }
+}
+
+fn render_messages(app: &App, scroll: u16, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let lines: Vec<Line> = app
+        .notifications
+        .log
+        .iter()
+        .rev()
+        .map(|notification| {
+            Line::from(vec![
+                Span::raw(notification.at.format("%H:%M:%S ").to_string()).fg(app.theme.dim),
+                Span::raw(format!("{:<6}", notification.severity.label()))
+                    .fg(severity_color(app, notification.severity)),
+                Span::raw(format!("{}: ", notification.title)).fg(app.theme.header),
+                Span::raw(notification.message.lines().collect::<Vec<_>>().join(" "))
+                    .fg(app.theme.text),
+            ])
+        })
+        .collect();
+    let empty = lines.is_empty();
+    Paragraph::new(if empty {
+        vec![Line::from("No messages yet").fg(app.theme.dim)]
+    } else {
+        lines
+    })
+    .scroll((scroll, 0))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Messages")
+            .title_bottom(format!(
+                "<{}>/<{}> scroll, <{}> close",
+                app.keymap.key_hint(Context::Popup, Action::Up),
+                app.keymap.key_hint(Context::Popup, Action::Down),
+                app.keymap.key_hint(Context::Popup, Action::Close)
+            ))
+            .fg(app.theme.border),
+    )
+    .render(area, buf);
+}
+
+fn render_changes(app: &App, scroll: u16, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let lines: Vec<Line> = app
+        .changes
+        .iter()
+        .map(|change| {
+            let color = match change {
+                Change::Added(_) => app.theme.info,
+                Change::Removed(_) => app.theme.error,
+                _ => app.theme.warning,
+            };
+            Line::from(vec![
+                Span::raw(format!("{:<10}", change.label())).fg(color),
+                Span::raw(change.describe()).fg(app.theme.text),
+            ])
+        })
+        .collect();
+    let empty = lines.is_empty();
+    Paragraph::new(if empty {
+        vec![Line::from("Nothing changed in the last refresh").fg(app.theme.dim)]
+    } else {
+        lines
+    })
+    .scroll((scroll, 0))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Changes")
+            .title_bottom(format!(
+                "<{}>/<{}> scroll, <{}> close",
+                app.keymap.key_hint(Context::Popup, Action::Up),
+                app.keymap.key_hint(Context::Popup, Action::Down),
+                app.keymap.key_hint(Context::Popup, Action::Close)
+            ))
+            .fg(app.theme.border),
+    )
+    .render(area, buf);
+}
+
+/// The blocks that were left out of the timetable, each with its reason.
+fn render_problems(app: &App, scroll: u16, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let lines: Vec<Line> = if app.problems.is_empty() {
+        vec![Line::from("Every block of the timetable is shown").fg(app.theme.dim)]
+    } else {
+        app.problems
+            .iter()
+            .flat_map(|problem| {
+                [
+                    Line::from(problem.block.as_str()).fg(app.theme.warning),
+                    Line::from(format!("  {}", problem.reason)).fg(app.theme.text),
+                ]
+            })
+            .collect()
+    };
+    Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Invalid blocks")
+                .title_bottom(format!(
+                    "<{}>/<{}> scroll, <{}> close",
+                    app.keymap.key_hint(Context::Popup, Action::Up),
+                    app.keymap.key_hint(Context::Popup, Action::Down),
+                    app.keymap.key_hint(Context::Popup, Action::Close)
+                ))
+                .fg(app.theme.border),
+        )
+        .render(area, buf);
+}
+
+/// Hours of classes per day and per subject of the week shown.
+fn render_statistics(app: &App, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let stats = app.statistics();
+    let frame = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Statistics of {}", week_label(app)))
+        .title_bottom(format!(
+            "<{}> close",
+            app.keymap.key_hint(Context::Popup, Action::Close)
+        ))
+        .fg(app.theme.border);
+    let inner = frame.inner(area);
+    frame.render(area, buf);
+
+    let [summary, days_area, bottom] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Length(DAYS.len() as u16 + 2),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+    let [subjects_area, chart_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(bottom);
+
+    let busiest = stats
+        .busiest_day()
+        .map(|day| format!(", busiest on {}", DAYS[day]))
+        .unwrap_or_default();
+    let mut lines = vec![
+        Line::from(format!("{} hours of classes{}", stats.total_hours, busiest)).fg(app.theme.text),
+    ];
+    if stats.unpicked_groups {
+        lines.push(
+            Line::from(format!(
+                "Parallel sessions without a picked group are all counted, pick yours with <{}>",
+                app.keymap.key_hint(Context::Grid, Action::ToggleGroup)
+            ))
+            .fg(app.theme.dim),
+        );
+    }
+    Paragraph::new(lines).render(summary, buf);
+
+    let hour = |hour: Option<usize>| hour.map(|hour| format!("{}:00", hour)).unwrap_or_default();
+    let rows = stats.days.iter().enumerate().map(|(day, day_stats)| {
+        let gaps: Vec<String> = day_stats
+            .gaps
+            .iter()
+            .map(|(from, until)| format!("{}:00 - {}:00", from, until))
+            .collect();
+        Row::new([
+            DAYS[day].to_string(),
+            day_stats.hours.to_string(),
+            hour(day_stats.first),
+            hour(day_stats.last),
+            if gaps.is_empty() {
+                String::new()
+            } else {
+                format!("{}h ({})", day_stats.gap_hours(), gaps.join(", "))
+            },
+        ])
+        .fg(app.theme.text)
+    });
+    let days_table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Min(0),
+        ],
+    )
+    .header(Row::new(["Day", "Hours", "First", "Last", "Gaps"]).fg(app.theme.header));
+    Widget::render(days_table, days_area, buf);
+
+    let rows = stats.subjects.iter().map(|(subject, types)| {
+        let split: Vec<String> = types
+            .iter()
+            .map(|(r#type, hours)| format!("{} {}h", r#type, hours))
+            .collect();
+        Row::new([
+            subject.clone(),
+            split.join(", "),
+            types.values().sum::<usize>().to_string(),
+        ])
+        .fg(app.theme.text)
+    });
+    let subjects_table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(50),
+            Constraint::Percentage(35),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(Row::new(["Subject", "Types", "Hours"]).fg(app.theme.header));
+    Widget::render(subjects_table, subjects_area, buf);
+
+    let bars: Vec<Bar> = stats
+        .days
+        .iter()
+        .enumerate()
+        .map(|(day, day_stats)| {
+            Bar::default()
+                .value(day_stats.hours as u64)
+                .label(Line::from(&DAYS[day][..3]))
+                .style(app.theme.info)
+                .value_style(Style::new().fg(app.theme.info).reversed())
+        })
+        .collect();
+    BarChart::default()
+        .block(Block::default().title("Hours per day").fg(app.theme.header))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(3)
+        .bar_gap(1)
+        .label_style(app.theme.text)
+        .render(chart_area, buf);
+}
+
+fn render_events(app: &App, cursor: usize, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let items: Vec<ListItem> = app
+        .personal
+        .iter()
+        .map(|event| {
+            ListItem::new(format!(
+                "{:<10} {:>2}:00 - {:>2}:00  {}  {}",
+                DAYS.get(event.day).unwrap_or(&"?"),
+                event.time,
+                event.time + event.duration,
+                event.name,
+                event.location
+            ))
+            .fg(app.theme.text)
+        })
+        .collect();
+    let keys = &app.keymap;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("My events")
+        .title_bottom(format!(
+            "<{}> add, <{}> edit, <{}> delete, <{}> close",
+            keys.key_hint(Context::Popup, Action::New),
+            keys.key_hint(Context::Popup, Action::Edit),
+            keys.key_hint(Context::Popup, Action::Delete),
+            keys.key_hint(Context::Popup, Action::Close)
+        ))
+        .fg(app.theme.personal);
+    if items.is_empty() {
+        Paragraph::new("No events yet")
+            .fg(app.theme.dim)
+            .block(block)
+            .render(area, buf);
+        return;
+    }
+    let mut state = ListState::default().with_selected(Some(cursor));
+    StatefulWidget::render(
+        List::new(items)
+            .highlight_symbol("> ")
+            .highlight_style(Style::new().bold())
+            .block(block),
+        area,
+        buf,
+        &mut state,
+    );
+}
+
+fn render_event_form(app: &App, form: &EventForm, area: Rect, buf: &mut Buffer) {
+    let title = if form.editing.is_some() {
+        "Edit event"
+    } else {
+        "New event"
+    };
+    let fields = EventForm::LABELS.iter().zip(&form.values);
+    render_form(
+        app,
+        title,
+        fields,
+        form.field,
+        app.theme.personal,
+        area,
+        buf,
+    );
+}
+
+fn render_note_editor(app: &App, form: &NoteForm, area: Rect, buf: &mut Buffer) {
+    let title = format!("Note on {}", form.label);
+    let fields = NoteForm::LABELS.iter().zip(&form.values);
+    render_form(app, &title, fields, form.field, app.theme.border, area, buf);
+}
+
+/// A popup with one line of text input per field, the cursor in field `active`.
+fn render_form<'a>(
+    app: &App,
+    title: &str,
+    fields: impl Iterator<Item = (&'a &'a str, &'a String)>,
+    active: usize,
+    color: Color,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    Clear.render(area, buf);
+    let lines: Vec<Line> = fields
+        .enumerate()
+        .map(|(idx, (label, value))| {
+            let active = idx == active;
+            Line::from(vec![
+                Span::raw(if active { "> " } else { "  " }).fg(app.theme.text),
+                Span::raw(format!("{:<12}", label)).fg(app.theme.dim),
+                Span::raw(value.as_str()).fg(app.theme.text),
+                Span::raw(if active { "_" } else { "" }).fg(app.theme.text),
+            ])
+        })
+        .collect();
+    let keys = &app.keymap;
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(format!(
+                    "<{}> next field, <{}> save, <{}> cancel",
+                    keys.key_hint(Context::Input, Action::NextField),
+                    keys.key_hint(Context::Input, Action::Confirm),
+                    keys.key_hint(Context::Input, Action::Cancel)
+                ))
+                .fg(color),
+        )
+        .render(area, buf);
+}
+
+fn render_filters(app: &App, cursor: usize, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let items: Vec<ListItem> = app
+        .filter_entries()
+        .into_iter()
+        .map(|rule| {
+            let hidden = app.settings.filters.contains(&rule);
+            let line = format!(
+                "[{}] {:<10} {}",
+                if hidden { " " } else { "x" },
+                rule.field.label(),
+                rule.value
+            );
+            if hidden {
+                ListItem::new(line).fg(app.theme.dim)
+            } else {
+                ListItem::new(line).fg(app.theme.text)
+            }
+        })
+        .collect();
+    let mut state = ListState::default().with_selected(Some(cursor));
+    StatefulWidget::render(
+        List::new(items)
+            .highlight_symbol("> ")
+            .highlight_style(Style::new().bold())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Filters")
+                    .title_bottom(format!(
+                        "<{}> show/hide, <{}> close",
+                        app.keymap.key_hint(Context::Popup, Action::Toggle),
+                        app.keymap.key_hint(Context::Popup, Action::Close)
+                    ))
+                    .fg(app.theme.border),
+            ),
+        area,
+        buf,
+        &mut state,
+    );
+}
+
+fn render_details(app: &App, area: Rect, buf: &mut Buffer) {
+    let Some(block) = app.selected_block() else {
+        return;
+    };
+    Clear.render(area, buf);
+    let when = [
+        ("Day", DAYS.get(block.day).unwrap_or(&"?").to_string()),
+        (
+            "Time",
+            format!("{}:00 - {}:00", block.time, block.time + block.duration),
+        ),
+    ];
+    let mut rows = match block.origin {
+        Origin::Personal => vec![
+            ("Event", block.subject.name.clone()),
+            ("Location", block.classroom.clone()),
+            ("Added by", "You".to_string()),
+        ],
+        Origin::Source => vec![
+            ("Subject", block.subject.name.clone()),
+            ("Abbreviation", block.subject.abbreviation.clone()),
+            ("Type", block.subject.r#type.clone()),
+            ("Professor", block.professor.clone()),
+            ("Classroom", block.classroom.clone()),
+            ("Location", block.subject.location.clone()),
+        ],
+    };
+    rows.extend(when);
+    let (subject_note, block_note) = app.notes_for(&block);
+    for (label, note) in [("Subject note", subject_note), ("Note", block_note)] {
+        if let Some(note) = note {
+            if !note.text.is_empty() {
+                rows.push((label, note.text.clone()));
+            }
+            if !note.tags.is_empty() {
+                rows.push(("Tags", note.tag_line()));
             }
         }
     }
+    let dates = |dates: &[chrono::NaiveDate]| {
+        let dates: Vec<String> = dates
+            .iter()
+            .map(|date| date.format("%-d.%-m.%Y").to_string())
+            .collect();
+        dates.join(", ")
+    };
+    if block.valid_from.is_some() || block.valid_until.is_some() {
+        let bound = |date: Option<chrono::NaiveDate>| {
+            date.map(|date| date.format("%-d.%-m.%Y").to_string())
+                .unwrap_or_default()
+        };
+        rows.push((
+            "Valid",
+            format!("{} - {}", bound(block.valid_from), bound(block.valid_until)),
+        ));
+    }
+    if let Some(weeks) = block.weeks {
+        rows.push(("Weeks", weeks.label().to_string()));
+    }
+    if !block.dates.is_empty() {
+        rows.push(("Only on", dates(&block.dates)));
+    }
+    if !block.except.is_empty() {
+        rows.push(("Cancelled on", dates(&block.except)));
+    }
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::raw(format!("{:<14}", label)).fg(app.theme.dim),
+                Span::raw(value).fg(app.theme.text),
+            ])
+        })
+        .collect();
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(block.subject.name.as_str())
+                .title_bottom(format!(
+                    "<{}> close",
+                    app.keymap.key_hint(Context::Popup, Action::Close)
+                ))
+                .fg(get_color(&block.subject, &app.colors, app.theme.fallback)),
+        )
+        .render(area, buf);
+}
+
+fn render_help(app: &App, scroll: u16, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let mut lines: Vec<Line> = Vec::new();
+    for (context, rows) in app.help_sections() {
+        lines.push(Line::from(context.label()).bold().fg(app.theme.header));
+        for (keys, description) in rows {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<22}", keys)).fg(app.theme.text),
+                Span::raw(description).fg(app.theme.dim),
+            ]));
+        }
+        lines.push(Line::default());
+    }
+    Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Help")
+                .title_bottom(format!(
+                    "<{}>/<{}> scroll, <{}> close",
+                    app.keymap.key_hint(Context::Popup, Action::Up),
+                    app.keymap.key_hint(Context::Popup, Action::Down),
+                    app.keymap.key_hint(Context::Popup, Action::Close)
+                ))
+                .fg(app.theme.border),
+        )
+        .render(area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Subject name, lane and lane count of every placed block, in the order they were placed.
+    fn lanes(blocks: &[TimeBlock]) -> Vec<(String, usize, usize)> {
+        assign_lanes(blocks.to_vec())
+            .into_iter()
+            .map(|placed| (placed.block.subject.name, placed.lane, placed.lanes))
+            .collect()
+    }
+
+    fn lane(name: &str, lane: usize, lanes: usize) -> (String, usize, usize) {
+        (name.to_string(), lane, lanes)
+    }
+
+    #[test]
+    fn separate_blocks_take_the_whole_column() {
+        let blocks = [
+            TimeBlock::sample(0, 8, 2, "A", "P"),
+            TimeBlock::sample(0, 10, 1, "B", "P"),
+        ];
+        assert_eq!(lanes(&blocks), [lane("A", 0, 1), lane("B", 0, 1)]);
+    }
+
+    #[test]
+    fn a_block_starting_inside_another_gets_its_own_lane() {
+        let blocks = [
+            TimeBlock::sample(0, 9, 1, "Inside", "P"),
+            TimeBlock::sample(0, 8, 3, "Long", "P"),
+        ];
+        assert_eq!(lanes(&blocks), [lane("Long", 0, 2), lane("Inside", 1, 2)]);
+    }
+
+    #[test]
+    fn longer_blocks_come_first_on_equal_start() {
+        let blocks = [
+            TimeBlock::sample(0, 8, 1, "Short", "P"),
+            TimeBlock::sample(0, 8, 2, "Long", "P"),
+        ];
+        assert_eq!(lanes(&blocks), [lane("Long", 0, 2), lane("Short", 1, 2)]);
+    }
+
+    #[test]
+    fn freed_lanes_are_reused_within_a_cluster() {
+        // Long overlaps both short blocks, which follow each other in the second lane
+        let blocks = [
+            TimeBlock::sample(0, 8, 4, "Long", "P"),
+            TimeBlock::sample(0, 8, 2, "First", "P"),
+            TimeBlock::sample(0, 10, 2, "Second", "P"),
+        ];
+        assert_eq!(
+            lanes(&blocks),
+            [
+                lane("Long", 0, 2),
+                lane("First", 1, 2),
+                lane("Second", 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn clusters_get_their_own_lane_count() {
+        let blocks = [
+            TimeBlock::sample(0, 8, 2, "A", "P"),
+            TimeBlock::sample(0, 8, 2, "B", "P"),
+            TimeBlock::sample(0, 8, 2, "C", "P"),
+            TimeBlock::sample(0, 12, 1, "Alone", "P"),
+        ];
+        assert_eq!(
+            lanes(&blocks),
+            [
+                lane("A", 0, 3),
+                lane("B", 1, 3),
+                lane("C", 2, 3),
+                lane("Alone", 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_outside_of_the_grid_are_not_placed() {
+        let grid = grid_layout(Rect::new(0, 0, 100, 40));
+        let blocks = [
+            TimeBlock::sample(0, 6, 2, "Early", "P"),
+            TimeBlock::sample(1, 20, 2, "Late", "P"),
+            TimeBlock::sample(2, 10, 1, "Fine", "P"),
+        ];
+        let placed: Vec<String> = block_areas(&blocks, &grid.rows)
+            .into_iter()
+            .map(|(_, placed)| placed.block.subject.name)
+            .collect();
+        assert_eq!(placed, ["Fine"]);
+    }
 }
@@ -0,0 +1,141 @@
+use crate::app::TimeBlock;
+use crate::ui::{DAYS, FIRST_HOUR, LAST_HOUR};
+
+/// A block that is not drawn, with the reason why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Which block it is, e.g. `Math (P) on Monday at 8:00`
+    pub block: String,
+    pub reason: String,
+}
+
+impl Problem {
+    pub fn new(block: &TimeBlock, reason: String) -> Self {
+        let day = DAYS
+            .get(block.day)
+            .map(|day| day.to_string())
+            .unwrap_or_else(|| format!("day {}", block.day));
+        Problem {
+            block: format!(
+                "{} ({}) on {} at {}:00",
+                block.subject.name, block.subject.r#type, day, block.time
+            ),
+            reason,
+        }
+    }
+}
+
+/// Splits the blocks into the ones the grid can draw and the problems of the others.
+pub fn validate(blocks: Vec<TimeBlock>) -> (Vec<TimeBlock>, Vec<Problem>) {
+    let mut valid: Vec<TimeBlock> = Vec::new();
+    let mut problems = Vec::new();
+    for block in blocks {
+        match check(&block, &valid) {
+            Some(reason) => problems.push(Problem::new(&block, reason)),
+            None => valid.push(block),
+        }
+    }
+    (valid, problems)
+}
+
+/// What is wrong with the block, `valid` are the blocks checked before it.
+fn check(block: &TimeBlock, valid: &[TimeBlock]) -> Option<String> {
+    let Some(end) = block.time.checked_add(block.duration) else {
+        return Some(format!(
+            "Its start {} and duration {} are too large to be hours",
+            block.time, block.duration
+        ));
+    };
+    if block.day >= DAYS.len() {
+        Some(format!(
+            "Day {} is not a weekday, days go from 0 (Monday) to {} (Friday)",
+            block.day,
+            DAYS.len() - 1
+        ))
+    } else if block.duration == 0 {
+        Some("It lasts zero hours".into())
+    } else if end > 24 {
+        Some(format!("It ends past midnight, at {}:00", end))
+    } else if block.time < FIRST_HOUR || end > LAST_HOUR {
+        Some(format!(
+            "{}:00 - {}:00 is outside of the hours shown, {}:00 - {}:00",
+            block.time, end, FIRST_HOUR, LAST_HOUR
+        ))
+    } else if valid.contains(block) {
+        Some("It is listed twice".into())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The reason the block is skipped, if it is.
+    fn reason(block: TimeBlock) -> Option<String> {
+        let (_, problems) = validate(vec![block]);
+        problems.into_iter().next().map(|problem| problem.reason)
+    }
+
+    #[test]
+    fn blocks_within_the_week_are_valid() {
+        let blocks = vec![
+            TimeBlock::sample(0, FIRST_HOUR, 2, "Math", "P"),
+            TimeBlock::sample(4, LAST_HOUR - 1, 1, "Physics", "P"),
+        ];
+        let (valid, problems) = validate(blocks.clone());
+        assert_eq!(valid, blocks);
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn weekends_are_skipped() {
+        let reason = reason(TimeBlock::sample(5, 8, 2, "Math", "P")).unwrap();
+        assert!(reason.contains("Day 5 is not a weekday"), "{}", reason);
+    }
+
+    #[test]
+    fn blocks_without_a_duration_are_skipped() {
+        assert_eq!(
+            reason(TimeBlock::sample(0, 8, 0, "Math", "P")).as_deref(),
+            Some("It lasts zero hours")
+        );
+    }
+
+    #[test]
+    fn blocks_past_midnight_are_skipped() {
+        assert_eq!(
+            reason(TimeBlock::sample(0, 20, 6, "Math", "P")).as_deref(),
+            Some("It ends past midnight, at 26:00")
+        );
+    }
+
+    #[test]
+    fn blocks_outside_of_the_hours_shown_are_skipped() {
+        let early = reason(TimeBlock::sample(0, FIRST_HOUR - 1, 2, "Math", "P")).unwrap();
+        assert!(early.contains("outside of the hours shown"), "{}", early);
+        let late = reason(TimeBlock::sample(0, LAST_HOUR - 1, 2, "Math", "P")).unwrap();
+        assert!(late.contains("outside of the hours shown"), "{}", late);
+    }
+
+    #[test]
+    fn duplicates_are_skipped_once_the_first_is_kept() {
+        let block = TimeBlock::sample(0, 8, 2, "Math", "P");
+        let (valid, problems) = validate(vec![block.clone(), block.clone()]);
+        assert_eq!(valid, [block]);
+        assert_eq!(
+            problems,
+            [Problem {
+                block: "Math (P) on Monday at 8:00".into(),
+                reason: "It is listed twice".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn huge_hours_do_not_overflow() {
+        let reason = reason(TimeBlock::sample(0, usize::MAX, 2, "Math", "P")).unwrap();
+        assert!(reason.contains("too large"), "{}", reason);
+    }
+}